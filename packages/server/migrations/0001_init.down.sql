@@ -0,0 +1,2 @@
+-- 0001可能接管了已存在的表，删除会丢失原有数据，Migrator::rollback不会执行这里
+select 1;
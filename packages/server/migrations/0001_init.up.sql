@@ -0,0 +1,36 @@
+-- 引入迁移之前部署的数据库已经有这些表，这里不会重复创建，只记录为已执行
+create table if not exists accounts
+(
+    pk          varchar(64) primary key,
+    uname       varchar(128) not null unique,
+    nickname    varchar(128) not null default '',
+    email       varchar(256) not null default '',
+    description text         not null default '',
+    photo       text         not null default '',
+    site        text         not null default '',
+    create_time timestamp    not null,
+    update_time timestamp    not null default now()
+);
+
+create table if not exists articles
+(
+    pk          varchar(64) primary key,
+    title       text        not null,
+    body        jsonb       not null,
+    description text        not null default '',
+    keywords    text        not null default '',
+    status      integer     not null default 0,
+    template    integer     not null default 1,
+    creator     varchar(64) not null references accounts (pk),
+    create_time timestamp   not null,
+    update_time timestamp   not null
+);
+
+create index if not exists articles_status_update_time_index on articles (status, update_time desc);
+create index if not exists articles_creator_index on articles (creator);
+
+create table if not exists articles_views
+(
+    pk    varchar(64) primary key references articles (pk) on delete cascade,
+    views bigint not null default 0
+);
@@ -0,0 +1,2 @@
+alter table articles
+    add column delete_time timestamp null;
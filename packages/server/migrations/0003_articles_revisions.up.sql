@@ -0,0 +1,13 @@
+create table articles_revisions
+(
+    pk          varchar(64) primary key,
+    article     varchar(64) not null references articles (pk) on delete cascade,
+    title       text        not null,
+    body        jsonb       not null,
+    description text        not null default '',
+    keywords    text        not null default '',
+    author      varchar(64) not null references accounts (pk),
+    create_time timestamp   not null
+);
+
+create index articles_revisions_article_index on articles_revisions (article, create_time desc);
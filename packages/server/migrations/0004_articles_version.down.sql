@@ -0,0 +1,2 @@
+alter table articles
+    drop column if exists version;
@@ -0,0 +1,7 @@
+alter table accounts
+    drop column if exists role;
+
+drop index if exists articles_status_publish_at_index;
+
+alter table articles
+    drop column if exists publish_at;
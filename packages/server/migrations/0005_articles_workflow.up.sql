@@ -0,0 +1,10 @@
+alter table articles
+    add column publish_at timestamp null;
+
+create index articles_status_publish_at_index on articles (status, publish_at);
+
+-- 已有账号保持可以直接发布文章，新账号默认为author
+alter table accounts
+    add column role varchar(16) not null default 'editor';
+alter table accounts
+    alter column role set default 'author';
@@ -0,0 +1,6 @@
+alter table articles
+    drop column if exists excerpt;
+alter table articles
+    drop column if exists reading_minutes;
+alter table articles
+    drop column if exists word_count;
@@ -0,0 +1,7 @@
+-- 已有文章的统计数据为0，读取时根据正文计算，下次保存时写入
+alter table articles
+    add column word_count integer not null default 0;
+alter table articles
+    add column reading_minutes integer not null default 0;
+alter table articles
+    add column excerpt text not null default '';
@@ -0,0 +1,3 @@
+drop table if exists articles_slugs;
+alter table articles
+    drop column if exists slug;
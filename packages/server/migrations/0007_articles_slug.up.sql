@@ -0,0 +1,13 @@
+-- 已有文章的slug为空，下次保存时生成，在此之前通过pk访问
+alter table articles
+    add column slug varchar(128) unique;
+
+-- 标题修改后被替换的slug，访问时跳转到文章当前的地址
+create table articles_slugs
+(
+    slug        varchar(128) primary key,
+    article     varchar(64)  not null references articles (pk) on delete cascade,
+    create_time timestamp    not null
+);
+
+create index articles_slugs_article_index on articles_slugs (article);
@@ -0,0 +1,315 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+
+use crate::config::{ConfigHandle, ConfigOptions, ProximaConfig};
+use crate::handlers::State;
+use crate::migrations::Migrator;
+use crate::models::account::AccountRole;
+use crate::models::article::ArticleStatus;
+use crate::models::claims::issue_token;
+use crate::models::error::AppError;
+use crate::service::account::AccountService;
+use crate::service::article::ArticleService;
+use crate::service::scheduler;
+use crate::utils::render::RendererRegistry;
+use crate::{handlers, layers};
+
+#[derive(Parser, Debug)]
+#[clap(name = "proxima", version)]
+pub struct Cli {
+    /// toml或yaml格式的配置文件
+    #[clap(short, long, global = true, parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// 覆盖单个配置项，可重复指定
+    #[clap(
+        long = "set",
+        value_name = "KEY=VALUE",
+        global = true,
+        parse(try_from_str = parse_key_value)
+    )]
+    overrides: Vec<(String, String)>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 启动服务，未指定子命令时的默认行为
+    Serve {
+        #[clap(long)]
+        bind: Option<IpAddr>,
+        #[clap(short, long)]
+        port: Option<u16>,
+    },
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+    #[clap(subcommand)]
+    Db(DbCommand),
+    #[clap(subcommand)]
+    User(UserCommand),
+    #[clap(subcommand)]
+    Article(ArticleCommand),
+    #[clap(subcommand)]
+    Token(TokenCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// 校验配置，列出所有缺失或无效的配置项
+    Check,
+    /// 打印合并后的配置及每项的来源
+    Dump,
+}
+
+#[derive(Subcommand, Debug)]
+enum DbCommand {
+    /// 执行所有未执行的迁移
+    Migrate,
+    /// 列出每个迁移及其执行时间
+    Status,
+    /// 回滚最近执行的迁移
+    Rollback {
+        #[clap(long, default_value = "1")]
+        steps: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UserCommand {
+    Create {
+        #[clap(long)]
+        uname: String,
+        #[clap(long)]
+        nickname: String,
+        #[clap(long, default_value = "")]
+        email: String,
+        /// author或editor
+        #[clap(long, default_value = "editor", parse(try_from_str = parse_role))]
+        role: AccountRole,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ArticleCommand {
+    /// 列出所有状态的文章，包括已删除的
+    List {
+        #[clap(long, default_value = "0", parse(try_from_str = parse_offset))]
+        offset: i64,
+        #[clap(long, default_value = "20", parse(try_from_str = parse_limit))]
+        limit: i64,
+    },
+    Publish {
+        pk: String,
+    },
+    /// 改回草稿
+    Unpublish {
+        pk: String,
+    },
+    /// 在指定的UTC时间发布，格式为%Y-%m-%d %H:%M:%S
+    Schedule {
+        pk: String,
+        #[clap(long, parse(try_from_str = parse_datetime))]
+        at: chrono::NaiveDateTime,
+    },
+    Archive {
+        pk: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TokenCommand {
+    /// 为指定用户签发访问token
+    Issue {
+        #[clap(long)]
+        uname: String,
+        #[clap(long, default_value = "30")]
+        days: i64,
+    },
+}
+
+fn parse_role(s: &str) -> Result<AccountRole, String> {
+    AccountRole::parse(s).ok_or_else(|| format!("无效的角色: {}", s))
+}
+
+fn parse_datetime(s: &str) -> Result<chrono::NaiveDateTime, String> {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").map_err(|err| err.to_string())
+}
+
+fn parse_offset(s: &str) -> Result<i64, String> {
+    match s.parse::<i64>() {
+        Ok(value) if value >= 0 => Ok(value),
+        _ => Err(format!("offset必须是非负整数: {}", s)),
+    }
+}
+
+fn parse_limit(s: &str) -> Result<i64, String> {
+    match s.parse::<i64>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(format!("limit必须是正整数: {}", s)),
+    }
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    let index = s.find('=').ok_or_else(|| format!("缺少'=': {}", s))?;
+    Ok((s[..index].to_string(), s[index + 1..].to_string()))
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<(), AppError> {
+        let mut options = ConfigOptions {
+            file: self.config,
+            overrides: self.overrides,
+        };
+        let command = self.command.unwrap_or(Command::Serve {
+            bind: None,
+            port: None,
+        });
+
+        match command {
+            Command::Serve { bind, port } => {
+                if let Some(bind) = bind {
+                    options
+                        .overrides
+                        .push(("BIND_ADDRESS".to_string(), bind.to_string()));
+                }
+                if let Some(port) = port {
+                    options
+                        .overrides
+                        .push(("PORT".to_string(), port.to_string()));
+                }
+                serve(options).await
+            }
+            Command::Config(ConfigCommand::Check) => {
+                let config = ProximaConfig::init(options).await?;
+                println!("配置有效\n{}", config.dump());
+                Ok(())
+            }
+            Command::Config(ConfigCommand::Dump) => {
+                let config = ProximaConfig::init(options).await?;
+                println!("{}", config.dump());
+                Ok(())
+            }
+            Command::Db(command) => {
+                let config = ProximaConfig::init(options).await?;
+                let migrator = Migrator::new(layers::connect(config.dsn.as_str()).await?);
+                match command {
+                    DbCommand::Migrate => {
+                        let applied = migrator.migrate().await?;
+                        println!("已执行{}个迁移", applied.len());
+                    }
+                    DbCommand::Status => {
+                        for status in migrator.status().await? {
+                            let applied_at = status
+                                .applied_at
+                                .map_or("未执行".to_string(), |v| v.to_string());
+                            println!("{:04}_{}\t{}", status.version, status.name, applied_at);
+                        }
+                    }
+                    DbCommand::Rollback { steps } => {
+                        let rolled_back = migrator.rollback(steps).await?;
+                        println!("已回滚{}个迁移", rolled_back.len());
+                    }
+                }
+                Ok(())
+            }
+            Command::User(UserCommand::Create {
+                uname,
+                nickname,
+                email,
+                role,
+            }) => {
+                let state = admin_state(options).await?;
+                let pk = AccountService::new(state)
+                    .create(&uname, &nickname, &email, role)
+                    .await?;
+                println!("{}", pk);
+                Ok(())
+            }
+            Command::Article(command) => {
+                let state = admin_state(options).await?;
+                let article_service = ArticleService::new(state);
+                match command {
+                    ArticleCommand::List { offset, limit } => {
+                        for article in article_service.list_all(offset, limit).await? {
+                            let deleted = if article.delete_time.is_some() {
+                                "已删除"
+                            } else {
+                                "-"
+                            };
+                            println!(
+                                "{}\t{}\t{}\t{}",
+                                article.pk, article.status, deleted, article.title
+                            );
+                        }
+                    }
+                    ArticleCommand::Publish { pk } => {
+                        article_service
+                            .transition_as_admin(&pk, ArticleStatus::Published, None)
+                            .await?;
+                    }
+                    ArticleCommand::Unpublish { pk } => {
+                        article_service
+                            .transition_as_admin(&pk, ArticleStatus::Draft, None)
+                            .await?;
+                    }
+                    ArticleCommand::Schedule { pk, at } => {
+                        article_service
+                            .transition_as_admin(&pk, ArticleStatus::Scheduled, Some(at))
+                            .await?;
+                    }
+                    ArticleCommand::Archive { pk } => {
+                        article_service
+                            .transition_as_admin(&pk, ArticleStatus::Archived, None)
+                            .await?;
+                    }
+                }
+                Ok(())
+            }
+            Command::Token(TokenCommand::Issue { uname, days }) => {
+                let state = admin_state(options).await?;
+                let pk = AccountService::new(state.clone())
+                    .find_pk_by_uname(&uname)
+                    .await?
+                    .ok_or(AppError::NotFound)?;
+                let exp = (Utc::now() + Duration::days(days)).timestamp() as usize;
+                let token = issue_token(&state.config.current(), pk, exp)?;
+                println!("{}", token);
+                Ok(())
+            }
+        }
+    }
+}
+
+// 自定义的文章节点（callout、embed等）在这里通过register添加，同名时覆盖内置的实现
+fn node_renderers() -> RendererRegistry {
+    RendererRegistry::with_defaults()
+}
+
+async fn admin_state(options: ConfigOptions) -> Result<Arc<State>, AppError> {
+    let config = ProximaConfig::init(options.clone()).await?;
+    let config_handle = Arc::new(ConfigHandle::new(config, options));
+    Ok(handlers::build_state(config_handle, node_renderers()).await)
+}
+
+async fn serve(options: ConfigOptions) -> Result<(), AppError> {
+    let config = ProximaConfig::init(options.clone()).await?;
+    tracing::debug!("配置来源:\n{}", config.dump());
+
+    let addr = SocketAddr::new(config.bind_address, config.port);
+    tracing::debug!("listening on {}", addr);
+
+    let config_handle = Arc::new(ConfigHandle::new(config, options));
+    config_handle.clone().spawn_watch();
+    let state = handlers::build_state(config_handle, node_renderers()).await;
+    scheduler::spawn_publisher(state.clone());
+
+    axum::Server::bind(&addr)
+        .serve(handlers::app(state).into_make_service())
+        .await
+        .map_err(|err| AppError::Unknown(err.to_string()))?;
+    Ok(())
+}
@@ -1,83 +1,142 @@
-use crate::models::error::{AppError, OtherError};
-use aws_config::meta::region::RegionProviderChain;
-use aws_sdk_appconfig::Client;
-use std::collections::HashMap;
+pub(crate) mod loader;
+pub(crate) mod reload;
+pub(crate) mod schema;
 
-pub const FILE_URL: &str = "https://file.sfx.xyz";
-pub const DEFAULT_FILE_URL: &str = "https://res.sfx.xyz/images/default.png";
+use std::net::IpAddr;
+
+use crate::config::loader::{parse_pairs, ConfigLayer, ConfigLoader, ConfigSources};
+use crate::config::schema::ConfigReader;
+use crate::models::error::AppError;
+
+pub use loader::ConfigOptions;
+pub use reload::ConfigHandle;
 
 #[derive(Debug, Clone)]
 pub struct ProximaConfig {
+    pub storage: String,
     pub dsn: String,
     pub totp_secret: String,
     pub jwt_secret: String,
+    pub site_name: String,
+    pub site_url: String,
+    pub file_url: String,
+    pub default_file_url: String,
+    pub res_url: String,
+    pub debug_res_url: String,
+    pub index_page_size: i32,
+    pub bind_address: IpAddr,
+    pub port: u16,
+    pub reload_interval_seconds: u64,
+    pub jwt_grace_seconds: u64,
+    pub auto_migrate: bool,
+    pub render_cache_capacity: usize,
+    pub feed_size: i64,
+    pub feed_content: String,
+    pub sanitize_tags: Vec<String>,
+    pub sanitize_attributes: Vec<(String, String)>,
+    pub sanitize_url_schemes: Vec<String>,
+    pub allow_indexing: bool,
+    pub robots_disallow: Vec<String>,
+    pub security_contacts: Vec<String>,
+    pub security_policy: String,
+    pub security_expires_days: i64,
+    pub humans_team: Vec<String>,
+    pub sources: ConfigSources,
 }
 
 impl ProximaConfig {
-    pub async fn init() -> Result<ProximaConfig, AppError> {
-        let region_provider = RegionProviderChain::default_provider().or_else("ap-east-1");
-        let config = aws_config::from_env().region(region_provider).load().await;
+    pub async fn init(options: ConfigOptions) -> Result<ProximaConfig, AppError> {
+        let sources = ConfigLoader::new(options).load().await?;
+
+        ProximaConfig::from_sources(sources)
+    }
 
-        let client = Client::new(&config);
+    pub fn parse_config(configuration: &String) -> Result<ProximaConfig, AppError> {
+        let mut sources = ConfigSources::default();
+        for (key, value) in parse_pairs(configuration) {
+            sources.set(ConfigLayer::AppConfig, &key, value);
+        }
+        ProximaConfig::from_sources(sources)
+    }
 
-        let mut request = client
-            .get_configuration()
-            .client_id("proxima")
-            .application("sfx");
-        if is_debug() {
-            request = request.configuration("debug.config").environment("debug");
+    pub fn from_sources(sources: ConfigSources) -> Result<ProximaConfig, AppError> {
+        let mut reader = ConfigReader::new(&sources);
+        let storage = reader.one_of("STORAGE", "postgres", &["postgres", "memory"]);
+        // 内存存储仅用于本地调试和测试，不需要数据库
+        let dsn = if storage == "memory" {
+            reader.string("DSN", "")
         } else {
-            request = request
-                .configuration("release.config")
-                .environment("release");
+            reader.required("DSN")
         };
-        let response = request
-            .send()
-            .await
-            .map_err(|err| OtherError::Unknown(err))?;
-
-        if let Some(blob) = response.content() {
-            let data = blob.clone().into_inner();
-            let content = String::from_utf8(data).map_err(|err| OtherError::Unknown(err))?;
+        let totp_secret = reader.required("TOTP_SECRET");
+        let jwt_secret = reader.required("JWT_KEY");
+        let site_name = reader.string("SITE_NAME", "泛涵");
+        let site_url = reader.url("SITE_URL", "https://sfx.xyz");
+        let file_url = reader.url("FILE_URL", "https://file.sfx.xyz");
+        let default_file_url =
+            reader.url("DEFAULT_FILE_URL", "https://res.sfx.xyz/images/default.png");
+        let res_url = reader.url("RES_URL", "https://res.sfx.xyz");
+        let debug_res_url = reader.url("DEBUG_RES_URL", "http://127.0.0.1:3000");
+        let index_page_size = reader.range("INDEX_PAGE_SIZE", 10, 1, 100);
+        let bind_address = reader.parse("BIND_ADDRESS", IpAddr::from([0, 0, 0, 0]));
+        let port = reader.range("PORT", 8080, 1, 65535);
+        let reload_interval_seconds = reader.parse("RELOAD_INTERVAL_SECONDS", 60);
+        let jwt_grace_seconds = reader.parse("JWT_GRACE_SECONDS", 3600);
+        let auto_migrate = reader.parse("AUTO_MIGRATE", false);
+        let render_cache_capacity = reader.range("RENDER_CACHE_CAPACITY", 256, 0, 65536);
+        let feed_size = reader.range("FEED_SIZE", 20, 1, 100);
+        // full输出完整正文，excerpt只输出摘要
+        let feed_content = reader.one_of("FEED_CONTENT", "full", &["full", "excerpt"]);
+        // 在内置白名单之外额外允许的标签和属性，属性写作tag:attr，*表示所有标签
+        let sanitize_tags = reader.names("SANITIZE_TAGS", "");
+        let sanitize_attributes = reader.pairs("SANITIZE_ATTRIBUTES", "");
+        let sanitize_url_schemes = reader.names("SANITIZE_URL_SCHEMES", "http,https,mailto");
+        // 默认只有release模式允许搜索引擎收录，debug部署返回Disallow: /及X-Robots-Tag: noindex
+        let allow_indexing = reader.parse("ALLOW_INDEXING", !is_debug());
+        let robots_disallow = reader.list("ROBOTS_DISALLOW", "/graphql,/restful,/account");
+        // 未配置联系方式时不提供security.txt
+        let security_contacts = reader.list("SECURITY_CONTACTS", "");
+        let security_policy = reader.string("SECURITY_POLICY", "");
+        let security_expires_days = reader.range("SECURITY_EXPIRES_DAYS", 180, 1, 365);
+        let humans_team = reader.list("HUMANS_TEAM", "");
+        reader.finish().map_err(AppError::ConfigReport)?;
 
-            return ProximaConfig::parse_config(&content);
-        }
-        Err(AppError::EmptyData)
+        Ok(ProximaConfig {
+            storage,
+            dsn,
+            totp_secret,
+            jwt_secret,
+            site_name,
+            site_url,
+            file_url,
+            default_file_url,
+            res_url,
+            debug_res_url,
+            index_page_size,
+            bind_address,
+            port,
+            reload_interval_seconds,
+            jwt_grace_seconds,
+            auto_migrate,
+            render_cache_capacity,
+            feed_size,
+            feed_content,
+            sanitize_tags,
+            sanitize_attributes,
+            sanitize_url_schemes,
+            allow_indexing,
+            robots_disallow,
+            security_contacts,
+            security_policy,
+            security_expires_days,
+            humans_team,
+            sources,
+        })
     }
 
-    pub fn parse_config(configuration: &String) -> Result<ProximaConfig, AppError> {
-        let split = configuration.split("\n");
-        let mut config_map: HashMap<String, String> = HashMap::new();
-
-        let mut config = ProximaConfig {
-            dsn: "".to_string(),
-            totp_secret: "".to_string(),
-            jwt_secret: "".to_string(),
-        };
-        for s in split {
-            let index = s.find("=").unwrap_or(0);
-            if index > 0 {
-                config_map.insert(s[..index].to_string(), s[index + 1..].to_string());
-                let key = s[..index].to_string();
-                let value = s[index + 1..].to_string();
-                match key.as_str() {
-                    "DSN" => config.dsn = value,
-                    "TOTP_SECRET" => config.totp_secret = value,
-                    "JWT_KEY" => config.jwt_secret = value,
-                    _ => {}
-                }
-            }
-        }
-        if config.dsn.is_empty() {
-            return Err(AppError::InvalidConfig("未配置DSN"));
-        }
-        if config.totp_secret.is_empty() {
-            return Err(AppError::InvalidConfig("未配置TOTP_SECRET"));
-        }
-        if config.jwt_secret.is_empty() {
-            return Err(AppError::InvalidConfig("未配置JWT_SECRET"));
-        }
-        Ok(config)
+    // 列出每个配置项的值以及设置它的配置层
+    pub fn dump(&self) -> String {
+        self.sources.dump()
     }
 }
 
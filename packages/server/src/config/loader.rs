@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use aws_config::meta::region::RegionProviderChain;
+use aws_sdk_appconfig::Client;
+
+use crate::config::is_debug;
+use crate::models::error::{AppError, OtherError};
+
+pub const ENV_PREFIX: &str = "PROXIMA_";
+
+// 这些键在dump时只显示是否已设置，不显示内容
+const SECRET_KEYS: [&str; 3] = ["DSN", "TOTP_SECRET", "JWT_KEY"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLayer {
+    File,
+    AppConfig,
+    Env,
+    Cli,
+}
+
+impl Display for ConfigLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConfigLayer::File => "file",
+            ConfigLayer::AppConfig => "appconfig",
+            ConfigLayer::Env => "env",
+            ConfigLayer::Cli => "cli",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOptions {
+    pub file: Option<PathBuf>,
+    pub overrides: Vec<(String, String)>,
+}
+
+/// 合并后的配置键值，同时记录每个键最终由哪一层设置
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    values: BTreeMap<String, (String, ConfigLayer)>,
+}
+
+impl ConfigSources {
+    pub fn set(&mut self, layer: ConfigLayer, key: &str, value: String) {
+        self.values.insert(normalize_key(key), (value, layer));
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|(value, _)| value.as_str())
+    }
+
+    pub fn layer(&self, key: &str) -> Option<ConfigLayer> {
+        self.values.get(key).map(|(_, layer)| *layer)
+    }
+
+    // 使用other中key的值和来源，other中没有时删除
+    pub fn keep_from(&mut self, other: &ConfigSources, key: &str) {
+        match other.values.get(key) {
+            Some(entry) => {
+                self.values.insert(key.to_string(), entry.clone());
+            }
+            None => {
+                self.values.remove(key);
+            }
+        }
+    }
+
+    pub fn changed_keys(&self, other: &ConfigSources) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for key in self.values.keys().chain(other.values.keys()) {
+            if self.get(key) != other.get(key) && !keys.contains(key) {
+                keys.push(key.to_string());
+            }
+        }
+        keys
+    }
+
+    pub fn dump(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        for (key, (value, layer)) in &self.values {
+            let shown = if SECRET_KEYS.contains(&key.as_str()) {
+                "******"
+            } else {
+                value.as_str()
+            };
+            lines.push(format!("{:<24} = {:<40} [{}]", key, shown, layer));
+        }
+        lines.join("\n")
+    }
+}
+
+/// 按 配置文件 -> AppConfig -> PROXIMA_*环境变量 -> 命令行 的顺序加载配置，后加载的覆盖先加载的
+pub struct ConfigLoader {
+    options: ConfigOptions,
+}
+
+impl ConfigLoader {
+    pub fn new(options: ConfigOptions) -> ConfigLoader {
+        ConfigLoader { options }
+    }
+
+    pub async fn load(&self) -> Result<ConfigSources, AppError> {
+        let mut sources = ConfigSources::default();
+
+        let file = self.options.file.clone().or_else(|| {
+            env::var(format!("{}CONFIG", ENV_PREFIX))
+                .ok()
+                .map(PathBuf::from)
+        });
+        if let Some(path) = file {
+            for (key, value) in read_file(&path)? {
+                sources.set(ConfigLayer::File, &key, value);
+            }
+        }
+
+        let env_values = read_env_layer();
+
+        // AppConfig是否启用本身也可以由其他层决定，未设置时仅release模式默认启用
+        let appconfig_enabled = self
+            .options
+            .overrides
+            .iter()
+            .rev()
+            .find(|(key, _)| normalize_key(key) == "APPCONFIG_ENABLED")
+            .map(|(_, value)| value.as_str())
+            .or_else(|| {
+                env_values
+                    .iter()
+                    .find(|(key, _)| key == "APPCONFIG_ENABLED")
+                    .map(|(_, value)| value.as_str())
+            })
+            .or_else(|| sources.get("APPCONFIG_ENABLED"))
+            .map_or(!is_debug(), |value| value == "true" || value == "1");
+        if appconfig_enabled {
+            let content = fetch_appconfig().await?;
+            for (key, value) in parse_pairs(&content) {
+                sources.set(ConfigLayer::AppConfig, &key, value);
+            }
+        }
+
+        for (key, value) in env_values {
+            sources.set(ConfigLayer::Env, &key, value);
+        }
+        for (key, value) in &self.options.overrides {
+            sources.set(ConfigLayer::Cli, key, value.to_string());
+        }
+        Ok(sources)
+    }
+}
+
+pub fn normalize_key(key: &str) -> String {
+    key.trim().replace(['.', '-'], "_").to_uppercase()
+}
+
+/// 解析AppConfig中 KEY=VALUE 每行一项的格式
+pub fn parse_pairs(content: &str) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for s in content.split('\n') {
+        let index = s.find('=').unwrap_or(0);
+        if index > 0 {
+            pairs.push((s[..index].to_string(), s[index + 1..].to_string()));
+        }
+    }
+    pairs
+}
+
+fn read_env_layer() -> Vec<(String, String)> {
+    env::vars()
+        .filter(|(key, _)| key.starts_with(ENV_PREFIX) && key.len() > ENV_PREFIX.len())
+        .filter(|(key, _)| key != &format!("{}CONFIG", ENV_PREFIX))
+        .map(|(key, value)| (normalize_key(&key[ENV_PREFIX.len()..]), value))
+        .collect()
+}
+
+fn read_file(path: &Path) -> Result<Vec<(String, String)>, AppError> {
+    let content = std::fs::read_to_string(path).map_err(OtherError::Unknown)?;
+    let extension = path.extension().and_then(|v| v.to_str()).unwrap_or("");
+    let value: serde_json::Value = match extension {
+        "toml" => toml::from_str(&content).map_err(OtherError::Unknown)?,
+        "yaml" | "yml" => serde_yaml::from_str(&content).map_err(OtherError::Unknown)?,
+        _ => return Err(AppError::InvalidConfig("配置文件仅支持toml或yaml格式")),
+    };
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    flatten_value("", &value, &mut pairs);
+    Ok(pairs)
+}
+
+// 嵌套的表展开为下划线连接的键，如 [appconfig] enabled 展开为 APPCONFIG_ENABLED
+fn flatten_value(prefix: &str, value: &serde_json::Value, pairs: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let child_prefix = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}_{}", prefix, key)
+                };
+                flatten_value(&child_prefix, child, pairs);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            let joined: Vec<String> = items.iter().map(scalar_to_string).collect();
+            pairs.push((normalize_key(prefix), joined.join(",")));
+        }
+        serde_json::Value::Null => {}
+        _ => pairs.push((normalize_key(prefix), scalar_to_string(value))),
+    }
+}
+
+fn scalar_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(v) => v.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+async fn fetch_appconfig() -> Result<String, AppError> {
+    let region_provider = RegionProviderChain::default_provider().or_else("ap-east-1");
+    let config = aws_config::from_env().region(region_provider).load().await;
+
+    let client = Client::new(&config);
+
+    let mut request = client
+        .get_configuration()
+        .client_id("proxima")
+        .application("sfx");
+    if is_debug() {
+        request = request.configuration("debug.config").environment("debug");
+    } else {
+        request = request
+            .configuration("release.config")
+            .environment("release");
+    };
+    let response = request.send().await.map_err(OtherError::Unknown)?;
+
+    if let Some(blob) = response.content() {
+        let data = blob.clone().into_inner();
+        let content = String::from_utf8(data).map_err(OtherError::Unknown)?;
+        return Ok(content);
+    }
+    Err(AppError::EmptyData)
+}
@@ -0,0 +1,132 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::config::loader::{ConfigLoader, ConfigOptions};
+use crate::config::ProximaConfig;
+use crate::models::error::AppError;
+
+// 这些配置在启动时就已用于建立连接池、监听端口或注册模板helper，修改后需要重启才能生效
+pub const RESTART_REQUIRED_KEYS: [&str; 7] = [
+    "STORAGE",
+    "DSN",
+    "BIND_ADDRESS",
+    "PORT",
+    "RES_URL",
+    "DEBUG_RES_URL",
+    "RENDER_CACHE_CAPACITY",
+];
+
+#[derive(Debug)]
+struct PreviousSecret {
+    secret: String,
+    expires_at: Instant,
+}
+
+/// 持有当前生效的配置，重新加载时整体替换
+#[derive(Debug)]
+pub struct ConfigHandle {
+    options: ConfigOptions,
+    current: RwLock<Arc<ProximaConfig>>,
+    previous_jwt: RwLock<Option<PreviousSecret>>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    pub changed: Vec<String>,
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigHandle {
+    pub fn new(config: ProximaConfig, options: ConfigOptions) -> ConfigHandle {
+        ConfigHandle {
+            options,
+            current: RwLock::new(Arc::new(config)),
+            previous_jwt: RwLock::new(None),
+        }
+    }
+
+    pub fn current(&self) -> Arc<ProximaConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    // 当前密钥在前，轮换后的宽限期内还会返回上一个密钥
+    pub fn jwt_secrets(&self) -> Vec<String> {
+        let mut secrets = vec![self.current().jwt_secret.clone()];
+        if let Some(previous) = self.previous_jwt.read().unwrap().as_ref() {
+            if previous.expires_at > Instant::now() {
+                secrets.push(previous.secret.clone());
+            }
+        }
+        secrets
+    }
+
+    pub async fn reload(&self) -> Result<ReloadReport, AppError> {
+        let sources = ConfigLoader::new(self.options.clone()).load().await?;
+        let mut next = ProximaConfig::from_sources(sources)?;
+        let current = self.current();
+
+        let mut report = ReloadReport::default();
+        let changed = next.sources.changed_keys(&current.sources);
+        if changed.is_empty() {
+            return Ok(report);
+        }
+        for key in changed {
+            if RESTART_REQUIRED_KEYS.contains(&key.as_str()) {
+                report.restart_required.push(key);
+            } else {
+                report.changed.push(key);
+            }
+        }
+        // 需要重启的配置保持旧值，避免配置与实际运行状态不一致
+        next.storage = current.storage.clone();
+        next.dsn = current.dsn.clone();
+        next.bind_address = current.bind_address;
+        next.port = current.port;
+        next.res_url = current.res_url.clone();
+        next.debug_res_url = current.debug_res_url.clone();
+        next.render_cache_capacity = current.render_cache_capacity;
+        // 配置来源也保留旧值，输出的配置与实际生效的一致
+        for key in RESTART_REQUIRED_KEYS {
+            next.sources.keep_from(&current.sources, key);
+        }
+
+        if next.jwt_secret != current.jwt_secret {
+            let grace = Duration::from_secs(next.jwt_grace_seconds);
+            *self.previous_jwt.write().unwrap() = Some(PreviousSecret {
+                secret: current.jwt_secret.clone(),
+                expires_at: Instant::now() + grace,
+            });
+        }
+        *self.current.write().unwrap() = Arc::new(next);
+        Ok(report)
+    }
+
+    pub fn spawn_watch(self: Arc<Self>) {
+        let seconds = self.current().reload_interval_seconds;
+        if seconds == 0 {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+            // 第一次tick会立即完成，跳过它
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match self.reload().await {
+                    Ok(report) => {
+                        if !report.changed.is_empty() {
+                            tracing::info!("配置已重新加载: {}", report.changed.join(", "));
+                        }
+                        if !report.restart_required.is_empty() {
+                            tracing::warn!(
+                                "以下配置修改后需要重启才能生效: {}",
+                                report.restart_required.join(", ")
+                            );
+                        }
+                    }
+                    Err(err) => tracing::warn!("重新加载配置出错: {}", err),
+                }
+            }
+        });
+    }
+}
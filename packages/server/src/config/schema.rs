@@ -0,0 +1,128 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::config::loader::ConfigSources;
+
+/// 读取并校验配置项，出错时不立即返回，而是收集起来统一报告
+pub struct ConfigReader<'a> {
+    sources: &'a ConfigSources,
+    errors: Vec<String>,
+}
+
+impl<'a> ConfigReader<'a> {
+    pub fn new(sources: &'a ConfigSources) -> ConfigReader<'a> {
+        ConfigReader {
+            sources,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn required(&mut self, key: &str) -> String {
+        match self.sources.get(key) {
+            Some(value) if !value.is_empty() => value.to_string(),
+            _ => {
+                self.errors.push(format!("{}: 未配置", key));
+                "".to_string()
+            }
+        }
+    }
+
+    pub fn string(&mut self, key: &str, default: &str) -> String {
+        self.sources.get(key).unwrap_or(default).to_string()
+    }
+
+    // 站点地址统一去掉末尾的'/'，方便直接拼接路径
+    pub fn url(&mut self, key: &str, default: &str) -> String {
+        let value = self.string(key, default);
+        if !value.starts_with("http://") && !value.starts_with("https://") {
+            self.invalid(key, &value, "必须以http://或https://开头");
+        }
+        value.trim_end_matches('/').to_string()
+    }
+
+    // 逗号分隔的列表，忽略空项
+    pub fn list(&mut self, key: &str, default: &str) -> Vec<String> {
+        self.string(key, default)
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    // 标签、属性等不区分大小写的名称，统一转为小写
+    pub fn names(&mut self, key: &str, default: &str) -> Vec<String> {
+        self.list(key, default)
+            .into_iter()
+            .map(|item| item.to_lowercase())
+            .collect()
+    }
+
+    pub fn pairs(&mut self, key: &str, default: &str) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        for item in self.names(key, default) {
+            match item.split_once(':') {
+                Some((left, right)) if !left.is_empty() && !right.is_empty() => {
+                    pairs.push((left.to_string(), right.to_string()))
+                }
+                _ => self.invalid(key, &item, "格式应为tag:attr"),
+            }
+        }
+        pairs
+    }
+
+    pub fn one_of(&mut self, key: &str, default: &str, values: &[&str]) -> String {
+        let value = self.string(key, default);
+        if !values.contains(&value.as_str()) {
+            self.invalid(key, &value, &format!("可选值{}", values.join("/")));
+        }
+        value
+    }
+
+    pub fn parse<T>(&mut self, key: &str, default: T) -> T
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        match self.sources.get(key) {
+            None => default,
+            Some(value) => match value.parse::<T>() {
+                Ok(v) => v,
+                Err(err) => {
+                    self.invalid(key, value, &err.to_string());
+                    default
+                }
+            },
+        }
+    }
+
+    pub fn range<T>(&mut self, key: &str, default: T, min: T, max: T) -> T
+    where
+        T: FromStr + PartialOrd + Display + Copy,
+        <T as FromStr>::Err: Display,
+    {
+        let value = self.parse(key, default);
+        if value < min || value > max {
+            self.invalid(key, &value.to_string(), &format!("取值范围{}~{}", min, max));
+        }
+        value
+    }
+
+    fn invalid(&mut self, key: &str, value: &str, reason: &str) {
+        let layer = self
+            .sources
+            .layer(key)
+            .map_or("default".to_string(), |v| v.to_string());
+        self.errors.push(format!(
+            "{}: 无效的值'{}' [{}], {}",
+            key, value, layer, reason
+        ));
+    }
+
+    pub fn finish(self) -> Result<(), Vec<String>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
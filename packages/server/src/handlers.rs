@@ -1,74 +1,127 @@
 use std::sync::Arc;
 
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::http::Method;
-use axum::response::Html;
-use axum::{extract::Extension, response::IntoResponse, routing::get, routing::post, Router};
-use bb8::Pool;
-use bb8_postgres::PostgresConnectionManager;
+use axum::middleware::from_fn;
+use axum::{routing::get, routing::post, Router};
 use handlebars::Handlebars;
-use tokio_postgres::NoTls;
 use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::ServiceBuilderExt;
 
-use crate::config::{is_debug, ProximaConfig};
+use crate::config::{is_debug, ConfigHandle};
 use crate::handlers::jwt::{login_handler, register_handler};
-use crate::models::claims::Claims;
+use crate::migrations::Migrator;
+use crate::repository::memory::MemoryRepository;
+use crate::repository::postgres::PostgresRepository;
+use crate::repository::{AccountRepository, ArticleRepository, RevisionRepository};
+use crate::utils::render::RendererRegistry;
+use crate::utils::render_cache::RenderCache;
 use crate::views::graphql::schema::{graphql_mutation_handler, graphql_mutation_playground};
 use crate::views::{html, restful};
 use crate::{config, helpers, layers};
 
 mod about;
 mod article;
+mod conditional;
+mod feed;
 mod jwt;
 mod sitemap;
 mod user;
+mod well_known;
 
 #[derive(Clone, Debug)]
 pub struct State {
     pub registry: Handlebars<'static>,
-    pub pool: layers::ConnectionPool,
-    pub config: ProximaConfig,
+    pub config: Arc<ConfigHandle>,
+    pub articles: Arc<dyn ArticleRepository>,
+    pub accounts: Arc<dyn AccountRepository>,
+    pub revisions: Arc<dyn RevisionRepository>,
+    pub renderers: Arc<RendererRegistry>,
+    pub render_cache: Arc<RenderCache>,
 }
 
-pub async fn app() -> Router {
-    let config = ProximaConfig::init().await.expect("初始化配置出错");
-
-    let dsn_env: &str = config.dsn.as_str();
+// renderers由调用方创建，自定义的文章节点（callout、embed等）在传入之前注册
+pub async fn build_state(
+    config_handle: Arc<ConfigHandle>,
+    renderers: RendererRegistry,
+) -> Arc<State> {
+    let config = config_handle.current();
+    if config.storage == "memory" {
+        let repository = Arc::new(MemoryRepository::new());
+        return new_state(
+            config_handle,
+            repository.clone(),
+            repository.clone(),
+            repository,
+            renderers,
+        );
+    }
 
-    let manager = PostgresConnectionManager::new_from_stringlike(dsn_env, NoTls).unwrap();
-    let pool = Pool::builder().build(manager).await.unwrap();
+    let pool = layers::connect(config.dsn.as_str()).await.unwrap();
+    if config.auto_migrate {
+        Migrator::new(pool.clone())
+            .migrate()
+            .await
+            .expect("执行数据库迁移出错");
+    }
+    let repository = Arc::new(PostgresRepository::new(pool));
+    new_state(
+        config_handle,
+        repository.clone(),
+        repository.clone(),
+        repository,
+        renderers,
+    )
+}
 
+pub fn new_state(
+    config_handle: Arc<ConfigHandle>,
+    articles: Arc<dyn ArticleRepository>,
+    accounts: Arc<dyn AccountRepository>,
+    revisions: Arc<dyn RevisionRepository>,
+    renderers: RendererRegistry,
+) -> Arc<State> {
+    let config = config_handle.current();
     let mut reg = Handlebars::new();
     if is_debug() {
         reg.set_dev_mode(true);
     }
-    reg.register_helper("reslink", Box::new(helpers::SimpleHelper));
+    let res_url = if is_debug() {
+        config.debug_res_url.clone()
+    } else {
+        config.res_url.clone()
+    };
+    reg.register_helper("reslink", Box::new(helpers::SimpleHelper { res_url }));
 
     register_template_file(&mut reg);
 
-    let state = Arc::new(State {
+    tracing::debug!("文章节点渲染器: {:?}", renderers);
+
+    Arc::new(State {
         registry: reg,
-        pool,
-        config,
-    });
+        config: config_handle,
+        articles,
+        accounts,
+        revisions,
+        renderers: Arc::new(renderers),
+        render_cache: Arc::new(RenderCache::new(config.render_cache_capacity)),
+    })
+}
 
+pub fn app(state: Arc<State>) -> Router {
     let cors = CorsLayer::new()
-        // allow `GET` and `POST` when accessing the resource
-        .allow_methods(vec![Method::GET, Method::POST])
+        // allow `GET`, `POST` and `PUT` when accessing the resource
+        .allow_methods(vec![Method::GET, Method::POST, Method::PUT])
         // allow requests from any origin
         .allow_origin(Any)
         .allow_headers(Any);
 
-    let middleware = ServiceBuilder::new().add_extension(state.clone());
+    let middleware = ServiceBuilder::new().add_extension(state);
 
     Router::new()
         .route("/", get(html::index::index_handler))
         .route("/about", get(about::about_handler))
-        .route("/article/read/:pk", get(article::article_read_handler))
+        .route("/article/*path", get(article::article_handler))
         .route(
             "/graphql/mutation",
             if config::is_debug() {
@@ -78,10 +131,26 @@ pub async fn app() -> Router {
             },
         )
         .route("/user/:pk", get(user::user_info_handler))
-        .route("/seo/sitemap", get(sitemap::sitemap_handler))
+        .route("/seo/sitemap", get(sitemap::legacy_sitemap_handler))
+        .route("/sitemap.xml", get(sitemap::sitemap_index_handler))
+        .route("/sitemap/:name", get(sitemap::sitemap_handler))
+        .route("/robots.txt", get(well_known::robots_handler))
+        .route("/humans.txt", get(well_known::humans_handler))
+        .route(
+            "/.well-known/security.txt",
+            get(well_known::security_handler),
+        )
+        .route("/feed.rss", get(feed::rss_handler))
+        .route("/feed.atom", get(feed::atom_handler))
+        .route("/feed.json", get(feed::json_feed_handler))
         .route("/account/login", post(login_handler))
         .route("/account/register", get(register_handler))
         .route("/restful/index/query", get(restful::index::query))
+        .route(
+            "/restful/article/:pk",
+            get(restful::article::read).put(restful::article::update),
+        )
+        .layer(from_fn(well_known::robots_tag_layer))
         .layer(cors)
         .layer(middleware.into_inner())
 }
@@ -105,6 +174,10 @@ fn register_template_file<'reg>(reg: &mut Handlebars) {
         .unwrap();
     reg.register_template_file("scripts", "assets/templates/partial/scripts.hbs")
         .unwrap();
+    reg.register_template_file("toc", "assets/templates/partial/toc.hbs")
+        .unwrap();
+    reg.register_template_file("meta", "assets/templates/partial/meta.hbs")
+        .unwrap();
 
     reg.register_template_file("article_read", "assets/templates/pages/article/read.hbs")
         .unwrap();
@@ -116,3 +189,216 @@ fn register_template_file<'reg>(reg: &mut Handlebars) {
     )
     .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::{Body, HttpBody};
+    use axum::http::{header, Request, StatusCode};
+    use axum::response::Response;
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::{app, new_state, State};
+    use crate::config::{ConfigHandle, ConfigOptions, ProximaConfig};
+    use crate::models::account::{AccountModel, AccountRole};
+    use crate::models::article::{ArticleModel, ArticleStatus};
+    use crate::models::claims::issue_token;
+    use crate::models::error::AppError;
+    use crate::repository::memory::MemoryRepository;
+    use crate::service::article::ArticleService;
+    use crate::utils::render::{RenderContext, RendererRegistry};
+
+    // 模板路径相对于仓库根目录
+    fn memory_state() -> Arc<State> {
+        std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../..")).unwrap();
+        let config =
+            ProximaConfig::parse_config(&"STORAGE=memory\nTOTP_SECRET=totp\nJWT_KEY=secret".into())
+                .unwrap();
+        let mut renderers = RendererRegistry::with_defaults();
+        renderers.register(
+            "callout",
+            |ctx: &mut RenderContext, node: &serde_json::Value| {
+                Ok(format!(
+                    "<div class='fx-callout'>{}</div>",
+                    ctx.render_children(node)?
+                ))
+            },
+        );
+        let repository = Arc::new(MemoryRepository::new());
+        new_state(
+            Arc::new(ConfigHandle::new(config, ConfigOptions::default())),
+            repository.clone(),
+            repository.clone(),
+            repository,
+            renderers,
+        )
+    }
+
+    async fn send(state: &Arc<State>, request: Request<Body>) -> Response {
+        app(state.clone()).oneshot(request).await.unwrap()
+    }
+
+    async fn body_string(response: Response) -> String {
+        let mut body = response.into_body();
+        let mut bytes: Vec<u8> = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn header_value(response: &Response, name: header::HeaderName) -> String {
+        response.headers()[name].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn article_update_and_slug_redirects() {
+        let state = memory_state();
+        let now = Utc::now().naive_utc();
+        state
+            .accounts
+            .insert(&AccountModel {
+                pk: "author".to_string(),
+                uname: "author".to_string(),
+                nickname: "作者".to_string(),
+                email: "".to_string(),
+                description: "".to_string(),
+                photo: "".to_string(),
+                site: "".to_string(),
+                role: AccountRole::Author,
+                create_time: now,
+            })
+            .await
+            .unwrap();
+        let service = ArticleService::new(state.clone());
+        let article = service
+            .create(ArticleModel {
+                pk: "a1".to_string(),
+                title: "Hello World".to_string(),
+                body: json!({
+                    "children": [{ "name": "callout", "children": [{ "text": "注意" }] }]
+                }),
+                description: "".to_string(),
+                keywords: "".to_string(),
+                status: ArticleStatus::Draft,
+                publish_at: None,
+                creator: "author".to_string(),
+                creator_nickname: "".to_string(),
+                create_time: now,
+                update_time: now,
+                delete_time: None,
+                views: 0,
+                version: 1,
+                slug: "".to_string(),
+                word_count: 0,
+                reading_minutes: 0,
+                excerpt: "".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(article.slug, "hello-world");
+
+        let exp = (Utc::now() + Duration::hours(1)).timestamp() as usize;
+        let token = issue_token(&state.config.current(), "author".to_string(), exp).unwrap();
+        let bearer = format!("Bearer {}", token);
+
+        // 作者可以读取自己的草稿
+        let response = send(
+            &state,
+            Request::get("/restful/article/a1")
+                .header(header::AUTHORIZATION, &bearer)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = header_value(&response, header::ETAG);
+        assert_eq!(etag, "\"1\"");
+
+        let update = |if_match: &str| {
+            Request::put("/restful/article/a1")
+                .header(header::AUTHORIZATION, &bearer)
+                .header(header::IF_MATCH, if_match)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({ "title": "新标题" }).to_string()))
+                .unwrap()
+        };
+        let response = send(&state, update(&etag)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, header::ETAG), "\"2\"");
+        let updated: serde_json::Value =
+            serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(updated["title"], "新标题");
+        assert_eq!(updated["slug"], "xin-biao-ti");
+
+        // 使用过期的ETag修改时返回409
+        let response = send(&state, update(&etag)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        service
+            .transition_as_admin("a1", ArticleStatus::Published, None)
+            .await
+            .unwrap();
+
+        // 旧的pk地址和旧slug都跳转到当前地址
+        for path in ["/article/read/a1", "/article/hello-world"] {
+            let response = send(&state, Request::get(path).body(Body::empty()).unwrap()).await;
+            assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+            assert_eq!(
+                header_value(&response, header::LOCATION),
+                "/article/xin-biao-ti"
+            );
+        }
+        let response = send(
+            &state,
+            Request::get("/article/read/a1.md")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            header_value(&response, header::LOCATION),
+            "/article/xin-biao-ti.md"
+        );
+
+        // 自定义的节点渲染器
+        let response = send(
+            &state,
+            Request::get("/article/xin-biao-ti")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_string(response)
+            .await
+            .contains("<div class=\"fx-callout\">"));
+
+        let response = send(
+            &state,
+            Request::get("/sitemap/articles-1.xml")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_string(response)
+            .await
+            .contains("<loc>https://sfx.xyz/article/xin-biao-ti</loc>"));
+
+        // 已删除的文章不能再变更状态
+        let mut deleted = service.find("a1").await.unwrap();
+        deleted.delete_time = Some(Utc::now().naive_utc());
+        service.update(deleted).await.unwrap();
+        assert!(matches!(
+            service
+                .transition_as_admin("a1", ArticleStatus::Draft, None)
+                .await,
+            Err(AppError::NotFound)
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::handlers::State;
 use crate::models::error::OtherError;
+use crate::utils::metadata::PageMeta;
 use crate::views::restful::error::HttpRESTError;
 use axum::response::Html;
 use axum::{extract::Extension, http::StatusCode};
@@ -9,9 +10,16 @@ use std::sync::Arc;
 pub async fn about_handler(
     Extension(state): Extension<Arc<State>>,
 ) -> Result<Html<String>, HttpRESTError> {
+    let config = state.config.current();
+    let page_meta = PageMeta::website(
+        &config,
+        &format!("关于 - {}", config.site_name),
+        "/about",
+        "",
+    );
     let result = state
         .registry
-        .render("about", &json!({}))
-        .map_err(|err| OtherError::Unknown(err))?;
+        .render("about", &json!({ "page_meta": page_meta }))
+        .map_err(OtherError::Unknown)?;
     Ok(Html(result))
 }
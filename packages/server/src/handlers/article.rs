@@ -8,98 +8,197 @@ use axum::response::{Html, IntoResponse, Response};
 use axum::{extract::Extension, extract::Path, http::StatusCode, BoxError, Json};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use nanoid::nanoid;
 use serde::de::DeserializeOwned;
 
 use crate::handlers::State;
+use crate::models::article::ArticleModel;
 use crate::models::claims::Claims;
 use crate::models::error::{AppError, OtherError};
 use crate::models::jwt::Protected;
-use crate::utils::article::{build_body, TocItem};
+use crate::service::account::AccountService;
+use crate::service::article::ArticleService;
+use crate::utils::article::Toc;
+use crate::utils::metadata::PageMeta;
+use crate::utils::render_cache::{RenderCache, RenderedArticle};
+use crate::utils::{export, sanitize};
 use crate::views::restful::error::HttpRESTError;
 use crate::{layers, utils};
 
-pub async fn article_read_handler(
-    Path(params): Path<HashMap<String, String>>,
+// /article/*path，包括/article/:slug和旧地址/article/read/:pk，
+// 两者无法作为两个路由同时注册；路由也无法直接匹配后缀，.md和.txt在这里区分
+pub async fn article_handler(
+    Path(path): Path<String>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<Html<String>, HttpRESTError> {
-    let pk = params.get("pk").ok_or_else(|| AppError::InvalidData)?;
-    tracing::debug!("pk:{}", pk,);
+) -> Result<Response, HttpRESTError> {
+    let path = path.trim_start_matches('/');
+    tracing::debug!("path:{}", path);
+    let (key, suffix) = match path.rsplit_once('.') {
+        Some((key, suffix)) if suffix == "md" || suffix == "txt" => (key, suffix),
+        Some(_) => return Err(AppError::NotFound.into()),
+        None => (path, ""),
+    };
 
-    let conn = state
-        .pool
-        .get()
-        .await
-        .map_err(|err| OtherError::BB8Postgres(err))?;
+    let article = match key.strip_prefix("read/") {
+        Some(pk) => {
+            let article = find_readable(&state, pk).await?;
+            // 已经有slug的文章跳转到规范地址
+            if !article.slug.is_empty() {
+                return Ok(redirect_to(&article, suffix));
+            }
+            article
+        }
+        None => {
+            let service = ArticleService::new(state.clone());
+            match service.find_by_slug(key).await? {
+                Some(article) => article,
+                None => {
+                    let article = service
+                        .find_by_old_slug(key)
+                        .await?
+                        .ok_or(AppError::NotFound)?;
+                    if !article.is_readable() {
+                        return Err(AppError::NotFound.into());
+                    }
+                    return Ok(redirect_to(&article, suffix));
+                }
+            }
+        }
+    };
+    if !article.is_readable() {
+        return Err(AppError::NotFound.into());
+    }
 
-    let query_result = conn
-        .query(
-            "select articles.pk, articles.title, articles.body, 
-articles.description, articles.update_time, articles.creator, articles.keywords,
-accounts.nickname, accounts.email, accounts.description, accounts.photo, 
-    accounts.create_time as accounts_create_time,
-articles_views.views
-from articles
-left join accounts on articles.creator = accounts.pk
-left join articles_views on articles.pk = articles_views.pk
-where articles.pk = $1;",
-            &[&pk],
-        )
-        .await
-        .map_err(|err| AppError::Postgresql(err))?;
+    match suffix {
+        "md" => {
+            let content = format!(
+                "# {}\n\n{}",
+                article.title,
+                export::document_to_markdown(&article.body)
+            );
+            Ok(export_response(
+                &article,
+                "text/markdown; charset=utf-8",
+                content,
+            ))
+        }
+        "txt" => {
+            let content = format!(
+                "{}\n\n{}",
+                article.title,
+                export::document_to_text(&article.body)
+            );
+            Ok(export_response(
+                &article,
+                "text/plain; charset=utf-8",
+                content,
+            ))
+        }
+        _ => article_page(state, article).await,
+    }
+}
 
-    if query_result.len() < 1 {
-        return Err(HttpRESTError::from(AppError::NotFound));
+fn redirect_to(article: &ArticleModel, suffix: &str) -> Response {
+    let mut path = article.path();
+    if !suffix.is_empty() {
+        path = format!("{}.{}", path, suffix);
     }
+    // Redirect::permanent返回308，旧地址按约定使用301
+    (StatusCode::MOVED_PERMANENTLY, [(header::LOCATION, path)]).into_response()
+}
 
-    let title: &str = query_result[0].get("title");
-    let body: serde_json::Value = query_result[0].get("body");
-    let description: &str = query_result[0].get("description");
-    let update_time: chrono::NaiveDateTime = query_result[0].get("update_time");
-    let creator: String = query_result[0].get("creator");
-    let keywords: String = query_result[0].get("keywords");
-    let views: Option<i64> = query_result[0].get("views");
-    let creator_nickname: &str = query_result[0].get("nickname");
-    let creator_email: Option<&str> = query_result[0].get("email");
-    let creator_description: Option<&str> = query_result[0].get("description");
-    let creator_photo: Option<&str> = query_result[0].get("photo");
-    let creator_create_time: chrono::NaiveDateTime = query_result[0].get("accounts_create_time");
+async fn article_page(state: Arc<State>, article: ArticleModel) -> Result<Response, HttpRESTError> {
+    let creator = AccountService::new(state.clone())
+        .find(&article.creator)
+        .await?;
 
-    let mut toc_list: Vec<TocItem> = Vec::new();
-    toc_list.push(TocItem {
-        title: title.to_string(),
-        header: 0,
-    });
-    let body_html =
-        build_body(&mut toc_list, &body).or_else(|err| Err(OtherError::Unknown(err)))?;
+    let config = state.config.current();
+    let rendered = render_article(&state, &article)?;
 
+    let reading = article.reading_stats();
     let page_data = &json!({
-        "pk": pk.to_string(),
-        "title": title.to_string(),
-        "body_html": body_html,
-        "description": description.to_string(),
-        "update_time_formatted": update_time.format("%Y年%m月%d日 %H:%M").to_string(),
+        "pk": article.pk,
+        "site_url": config.site_url,
+        "title": article.title,
+        "title_id": rendered.title_id,
+        "body_html": rendered.body_html,
+        "description": article.summary(),
+        "word_count": reading.word_count,
+        "reading_minutes": reading.reading_minutes,
+        "update_time_formatted": article.update_time.format("%Y年%m月%d日 %H:%M").to_string(),
         "creator": {
-            "pk": creator,
-            "email": creator_email.unwrap_or(""),
-            "description": creator_description.unwrap_or(""),
-            "nickname": creator_nickname.to_string(),
-            "photo": utils::get_photo_or_default(creator_photo.unwrap_or("")),
-            "create_time": creator_create_time.format("%Y年%m月%d日 %H:%M").to_string(),
+            "pk": creator.pk,
+            "email": creator.email,
+            "description": creator.description,
+            "nickname": creator.nickname,
+            "photo": utils::get_photo_or_default(&config, &creator.photo),
+            "create_time": creator.create_time.format("%Y年%m月%d日 %H:%M").to_string(),
         },
-        "views": views.unwrap_or(0),
-        "keywords": keywords,
-        "toc_list": toc_list,
+        "views": article.views,
+        "keywords": article.keywords,
+        "toc_list": rendered.toc,
+        "page_meta": PageMeta::article(&config, &article, &creator),
     });
     //println!("page_data: {:?}", page_data);
 
     let result = state
         .registry
         .render("article_read", page_data)
-        .map_err(|err| AppError::Handlebars(err))?;
+        .map_err(AppError::Handlebars)?;
+
+    Ok(([(header::ETAG, article.etag())], Html(result)).into_response())
+}
+
+// 渲染并清理正文，结果按内容缓存
+pub fn render_article(
+    state: &Arc<State>,
+    article: &ArticleModel,
+) -> Result<Arc<RenderedArticle>, OtherError<String>> {
+    let config = state.config.current();
+    let key = RenderCache::key(
+        &article.pk,
+        &(
+            &article.title,
+            article.body.to_string(),
+            &config.site_url,
+            &config.sanitize_tags,
+            &config.sanitize_attributes,
+            &config.sanitize_url_schemes,
+        ),
+    );
+    state
+        .render_cache
+        .get_or_render(&key, || {
+            // 文章标题作为目录的根节点
+            let mut toc = Toc::new();
+            let title_id = toc.add(&article.title, 0);
+            let body_html = state.renderers.build_body(&mut toc, &article.body)?;
+            Ok(RenderedArticle {
+                body_html: sanitize::sanitize_html(&config, &body_html),
+                title_id,
+                toc: toc.into_tree(),
+            })
+        })
+        .map_err(OtherError::Unknown)
+}
+
+async fn find_readable(state: &Arc<State>, pk: &str) -> Result<ArticleModel, AppError> {
+    let article = ArticleService::new(state.clone()).find(pk).await?;
+    if !article.is_readable() {
+        return Err(AppError::NotFound);
+    }
+    Ok(article)
+}
 
-    Ok(Html(result))
+fn export_response(article: &ArticleModel, content_type: &str, content: String) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::ETAG, article.etag()),
+        ],
+        content,
+    )
+        .into_response()
 }
@@ -0,0 +1,82 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+/// 条件请求的校验值，内容未变化时返回304
+#[derive(Debug, Clone)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: Option<NaiveDateTime>,
+}
+
+impl Validators {
+    // content包含会影响输出的所有数据，如文章pk和版本
+    pub fn new<T: Hash>(content: &T, last_modified: Option<NaiveDateTime>) -> Validators {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Validators {
+            etag: format!("\"{:016x}\"", hasher.finish()),
+            last_modified,
+        }
+    }
+
+    fn last_modified_header(&self) -> Option<String> {
+        self.last_modified.map(|time| {
+            Utc.from_utc_datetime(&time)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string()
+        })
+    }
+
+    // 有If-None-Match时忽略If-Modified-Since
+    pub fn is_not_modified(&self, headers: &HeaderMap) -> bool {
+        if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+            return value.to_str().map_or(false, |value| {
+                value.split(',').any(|tag| {
+                    let tag = tag.trim();
+                    tag == "*" || tag.trim_start_matches("W/") == self.etag
+                })
+            });
+        }
+        let since = headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+        match (since, self.last_modified) {
+            // HTTP日期只精确到秒
+            (Some(since), Some(last_modified)) => {
+                last_modified.timestamp() <= since.with_timezone(&Utc).timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, value);
+        }
+        if let Some(value) = self
+            .last_modified_header()
+            .and_then(|value| HeaderValue::from_str(&value).ok())
+        {
+            headers.insert(header::LAST_MODIFIED, value);
+        }
+        headers
+    }
+
+    pub fn not_modified(&self) -> Response {
+        (StatusCode::NOT_MODIFIED, self.headers()).into_response()
+    }
+
+    pub fn respond(&self, content_type: &str, content: String) -> Response {
+        let mut headers = self.headers();
+        if let Ok(value) = HeaderValue::from_str(content_type) {
+            headers.insert(header::CONTENT_TYPE, value);
+        }
+        (headers, content).into_response()
+    }
+}
@@ -0,0 +1,381 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Query};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use xml::writer::{EmitterConfig, EventWriter, Result as XmlResult, XmlEvent};
+
+use crate::config::ProximaConfig;
+use crate::handlers::article::render_article;
+use crate::handlers::conditional::Validators;
+use crate::handlers::State;
+use crate::models::article::ArticleModel;
+use crate::models::error::OtherError;
+use crate::service::account::AccountService;
+use crate::service::article::ArticleService;
+use crate::views::restful::error::HttpRESTError;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FeedQuery {
+    // 只包含指定作者的文章
+    pub author: Option<String>,
+    // 只包含带有指定关键词的文章
+    pub keyword: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    fn path(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "/feed.rss",
+            FeedFormat::Atom => "/feed.atom",
+            FeedFormat::Json => "/feed.json",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+struct FeedItem {
+    article: ArticleModel,
+    // 基于pk，修改标题后slug变化时保持不变，避免阅读器重复显示
+    id: String,
+    url: String,
+    author_url: String,
+    // 完整正文的HTML，配置为只输出摘要时为None
+    content_html: Option<String>,
+    summary: String,
+}
+
+struct Feed {
+    title: String,
+    description: String,
+    home_url: String,
+    feed_url: String,
+    updated: Option<NaiveDateTime>,
+    items: Vec<FeedItem>,
+}
+
+pub async fn rss_handler(
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, HttpRESTError> {
+    feed_response(FeedFormat::Rss, query, headers, state).await
+}
+
+pub async fn atom_handler(
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, HttpRESTError> {
+    feed_response(FeedFormat::Atom, query, headers, state).await
+}
+
+pub async fn json_feed_handler(
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, HttpRESTError> {
+    feed_response(FeedFormat::Json, query, headers, state).await
+}
+
+async fn feed_response(
+    format: FeedFormat,
+    query: FeedQuery,
+    headers: HeaderMap,
+    state: Arc<State>,
+) -> Result<Response, HttpRESTError> {
+    let config = state.config.current();
+    let author = query.author.as_deref().filter(|author| !author.is_empty());
+    let keyword = query
+        .keyword
+        .as_deref()
+        .filter(|keyword| !keyword.is_empty());
+
+    let mut title = config.site_name.clone();
+    if let Some(author) = author {
+        let account = AccountService::new(state.clone()).find(author).await?;
+        title = format!("{} - {}", title, account.nickname);
+    }
+    if let Some(keyword) = keyword {
+        title = format!("{} - {}", title, keyword);
+    }
+
+    let articles = ArticleService::new(state.clone())
+        .list_feed(author, keyword, config.feed_size)
+        .await?;
+    let updated = articles.iter().map(|article| article.update_time).max();
+    let versions: Vec<(&str, i64)> = articles
+        .iter()
+        .map(|article| (article.pk.as_str(), article.version))
+        .collect();
+    // 标题中包含站点名称，配置热加载后站点名称或地址变化时ETag随之变化
+    let validators = Validators::new(
+        &(
+            format,
+            author,
+            keyword,
+            &title,
+            &config.site_url,
+            &config.feed_content,
+            versions,
+        ),
+        updated,
+    );
+    if validators.is_not_modified(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let mut items: Vec<FeedItem> = Vec::new();
+    for article in articles {
+        let content_html = if config.feed_content == "full" {
+            Some(render_article(&state, &article)?.body_html.clone())
+        } else {
+            None
+        };
+        items.push(FeedItem {
+            id: format!("{}/article/read/{}", config.site_url, article.pk),
+            url: format!("{}{}", config.site_url, article.path()),
+            author_url: format!("{}/user/{}", config.site_url, article.creator),
+            content_html,
+            summary: article.summary(),
+            article,
+        });
+    }
+
+    let feed = Feed {
+        title,
+        description: config.site_name.clone(),
+        home_url: config.site_url.clone(),
+        feed_url: feed_url(&config, format, author, keyword),
+        updated,
+        items,
+    };
+    let content = match format {
+        FeedFormat::Rss => write_rss(&feed).map_err(OtherError::Unknown)?,
+        FeedFormat::Atom => write_atom(&feed).map_err(OtherError::Unknown)?,
+        FeedFormat::Json => write_json_feed(&feed),
+    };
+    Ok(validators.respond(format.content_type(), content))
+}
+
+fn feed_url(
+    config: &ProximaConfig,
+    format: FeedFormat,
+    author: Option<&str>,
+    keyword: Option<&str>,
+) -> String {
+    let mut params: Vec<String> = Vec::new();
+    if let Some(author) = author {
+        params.push(format!("author={}", encode_query(author)));
+    }
+    if let Some(keyword) = keyword {
+        params.push(format!("keyword={}", encode_query(keyword)));
+    }
+    let mut url = format!("{}{}", config.site_url, format.path());
+    if !params.is_empty() {
+        url.push('?');
+        url.push_str(&params.join("&"));
+    }
+    url
+}
+
+fn encode_query(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn rfc2822(time: &NaiveDateTime) -> String {
+    Utc.from_utc_datetime(time).to_rfc2822()
+}
+
+fn rfc3339(time: &NaiveDateTime) -> String {
+    Utc.from_utc_datetime(time).to_rfc3339()
+}
+
+fn write_text<W: Write>(writer: &mut EventWriter<W>, name: &str, text: &str) -> XmlResult<()> {
+    writer.write(XmlEvent::start_element(name))?;
+    writer.write(XmlEvent::characters(text))?;
+    writer.write(XmlEvent::end_element())
+}
+
+fn into_string(output: Vec<u8>) -> String {
+    String::from_utf8_lossy(&output).to_string()
+}
+
+fn write_rss(feed: &Feed) -> XmlResult<String> {
+    let mut output: Vec<u8> = Vec::new();
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(&mut output);
+    writer.write(
+        XmlEvent::start_element("rss")
+            .attr("version", "2.0")
+            .ns("atom", "http://www.w3.org/2005/Atom")
+            .ns("dc", "http://purl.org/dc/elements/1.1/"),
+    )?;
+    writer.write(XmlEvent::start_element("channel"))?;
+    write_text(&mut writer, "title", &feed.title)?;
+    write_text(&mut writer, "link", &feed.home_url)?;
+    write_text(&mut writer, "description", &feed.description)?;
+    writer.write(
+        XmlEvent::start_element("atom:link")
+            .attr("href", &feed.feed_url)
+            .attr("rel", "self")
+            .attr("type", "application/rss+xml"),
+    )?;
+    writer.write(XmlEvent::end_element())?;
+    if let Some(updated) = &feed.updated {
+        write_text(&mut writer, "lastBuildDate", &rfc2822(updated))?;
+    }
+
+    for item in &feed.items {
+        writer.write(XmlEvent::start_element("item"))?;
+        write_text(&mut writer, "title", &item.article.title)?;
+        write_text(&mut writer, "link", &item.url)?;
+        writer.write(XmlEvent::start_element("guid").attr("isPermaLink", "false"))?;
+        writer.write(XmlEvent::characters(&item.id))?;
+        writer.write(XmlEvent::end_element())?;
+        write_text(&mut writer, "dc:creator", &item.article.creator_nickname)?;
+        write_text(&mut writer, "pubDate", &rfc2822(&item.article.update_time))?;
+        for keyword in item.article.keyword_list() {
+            write_text(&mut writer, "category", &keyword)?;
+        }
+        let description = item.content_html.as_deref().unwrap_or(&item.summary);
+        write_text(&mut writer, "description", description)?;
+        writer.write(XmlEvent::end_element())?;
+    }
+
+    writer.write(XmlEvent::end_element())?;
+    writer.write(XmlEvent::end_element())?;
+    drop(writer);
+    Ok(into_string(output))
+}
+
+fn write_atom(feed: &Feed) -> XmlResult<String> {
+    let mut output: Vec<u8> = Vec::new();
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(&mut output);
+    // 正文中的站内链接是相对地址，通过xml:base解析
+    writer.write(
+        XmlEvent::start_element("feed")
+            .default_ns("http://www.w3.org/2005/Atom")
+            .attr("xml:base", &feed.home_url),
+    )?;
+    write_text(&mut writer, "title", &feed.title)?;
+    write_text(&mut writer, "subtitle", &feed.description)?;
+    write_text(&mut writer, "id", &feed.feed_url)?;
+    writer.write(
+        XmlEvent::start_element("link")
+            .attr("href", &feed.home_url)
+            .attr("rel", "alternate"),
+    )?;
+    writer.write(XmlEvent::end_element())?;
+    writer.write(
+        XmlEvent::start_element("link")
+            .attr("href", &feed.feed_url)
+            .attr("rel", "self"),
+    )?;
+    writer.write(XmlEvent::end_element())?;
+    let updated = feed.updated.unwrap_or_else(|| Utc::now().naive_utc());
+    write_text(&mut writer, "updated", &rfc3339(&updated))?;
+
+    for item in &feed.items {
+        writer.write(XmlEvent::start_element("entry"))?;
+        write_text(&mut writer, "title", &item.article.title)?;
+        write_text(&mut writer, "id", &item.id)?;
+        writer.write(
+            XmlEvent::start_element("link")
+                .attr("href", &item.url)
+                .attr("rel", "alternate"),
+        )?;
+        writer.write(XmlEvent::end_element())?;
+        write_text(
+            &mut writer,
+            "published",
+            &rfc3339(&item.article.create_time),
+        )?;
+        write_text(&mut writer, "updated", &rfc3339(&item.article.update_time))?;
+        writer.write(XmlEvent::start_element("author"))?;
+        write_text(&mut writer, "name", &item.article.creator_nickname)?;
+        write_text(&mut writer, "uri", &item.author_url)?;
+        writer.write(XmlEvent::end_element())?;
+        for keyword in item.article.keyword_list() {
+            writer.write(XmlEvent::start_element("category").attr("term", &keyword))?;
+            writer.write(XmlEvent::end_element())?;
+        }
+        write_text(&mut writer, "summary", &item.summary)?;
+        if let Some(content_html) = &item.content_html {
+            writer.write(XmlEvent::start_element("content").attr("type", "html"))?;
+            writer.write(XmlEvent::characters(content_html))?;
+            writer.write(XmlEvent::end_element())?;
+        }
+        writer.write(XmlEvent::end_element())?;
+    }
+
+    writer.write(XmlEvent::end_element())?;
+    drop(writer);
+    Ok(into_string(output))
+}
+
+// https://www.jsonfeed.org/version/1.1/
+fn write_json_feed(feed: &Feed) -> String {
+    let items: Vec<serde_json::Value> = feed
+        .items
+        .iter()
+        .map(|item| {
+            let mut value = json!({
+                "id": item.id,
+                "url": item.url,
+                "title": item.article.title,
+                "summary": item.summary,
+                "date_published": rfc3339(&item.article.create_time),
+                "date_modified": rfc3339(&item.article.update_time),
+                "authors": [{
+                    "name": item.article.creator_nickname,
+                    "url": item.author_url,
+                }],
+                "tags": item.article.keyword_list(),
+            });
+            match &item.content_html {
+                Some(content_html) => value["content_html"] = json!(content_html),
+                None => value["content_text"] = json!(item.summary),
+            }
+            value
+        })
+        .collect();
+    json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": feed.title,
+        "description": feed.description,
+        "home_page_url": feed.home_url,
+        "feed_url": feed.feed_url,
+        "items": items,
+    })
+    .to_string()
+}
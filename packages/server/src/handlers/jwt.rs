@@ -24,8 +24,9 @@ use totp_rs::{Algorithm, TOTP};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::handlers::State;
-use crate::models::claims::{AuthBody, AuthPayload, Claims, Keys};
+use crate::models::claims::{issue_token, AuthBody, AuthPayload, Claims, Keys};
 use crate::models::error::{AppError, OtherError};
+use crate::service::account::AccountService;
 use crate::views::restful::error::HttpRESTError;
 
 #[derive(Deserialize)]
@@ -44,7 +45,8 @@ pub async fn register_handler(
     if account.is_empty() {
         return Err(HttpRESTError::from(AppError::EmptyData));
     }
-    let secret = &state.config.totp_secret;
+    let config = state.config.current();
+    let secret = &config.totp_secret;
     let totp = TOTP::new(
         Algorithm::SHA1,
         6,
@@ -68,7 +70,7 @@ pub async fn register_handler(
     let result = state
         .registry
         .render("account_register", page_data)
-        .map_err(|err| OtherError::Unknown(err))?;
+        .map_err(OtherError::Unknown)?;
 
     Ok(Html(result))
 }
@@ -81,7 +83,8 @@ pub async fn login_handler(
     if payload.account.is_empty() {
         return Err(HttpRESTError::from(AppError::MissingCredentials));
     }
-    let secret = &state.config.totp_secret;
+    let config = state.config.current();
+    let secret = &config.totp_secret;
     let totp = TOTP::new(
         Algorithm::SHA1,
         6,
@@ -95,37 +98,19 @@ pub async fn login_handler(
 
     let ok = totp
         .check_current(payload.code.as_str())
-        .map_err(|err| OtherError::Unknown(err))?;
+        .map_err(OtherError::Unknown)?;
     if !ok {
         return Err(HttpRESTError::from(AppError::WrongCredentials));
     }
 
-    let conn = state
-        .pool
-        .get()
-        .await
-        .map_err(|err| OtherError::Unknown(err))?;
+    let account_service = AccountService::new(state.clone());
+    let pk = account_service
+        .find_pk_by_uname(&payload.account)
+        .await?
+        .ok_or(AppError::WrongCredentials)?;
 
-    let uname = &payload.account;
-    let query_result = conn
-        .query("select accounts.pk from accounts where uname=$1", &[&uname])
-        .await
-        .map_err(|err| OtherError::Unknown(err))?;
-
-    if query_result.len() < 1 {
-        return Err(HttpRESTError::from(AppError::WrongCredentials));
-    }
-
-    let pk: String = query_result[0].get("pk");
-
-    let claims = Claims {
-        exp: 2000000000, // May 2033
-        user: pk,
-    };
-    let jwt_keys = Keys::new(&state.config.jwt_secret.as_bytes());
     // Create the authorization token
-    let token = encode(&Header::default(), &claims, &jwt_keys.encoding)
-        .map_err(|err| OtherError::Unknown(err))?;
+    let token = issue_token(&config, pk, 2000000000)?; // May 2033
 
     // Send the authorized token
     Ok(Json(AuthBody::new(token)))
@@ -1,89 +1,250 @@
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::response::Html;
-use axum::{extract::Extension, http::StatusCode};
-use chrono::{TimeZone, Utc};
+use axum::body::{boxed, Body, Bytes};
+use axum::extract::{Extension, Path};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use xml::escape::escape_str_pcdata;
 
 use crate::handlers::State;
-use crate::layers;
-
-use crate::models::error::OtherError;
+use crate::models::error::{AppError, OtherError};
+use crate::utils::article;
 use crate::views::restful::error::HttpRESTError;
-use xml::writer::{EmitterConfig, XmlEvent};
 
-pub async fn sitemap_handler<'a>(
+// 单个sitemap最多包含50000个地址
+const SITEMAP_MAX_URLS: i64 = 50000;
+// 生成文章sitemap时每次从数据库读取的数量，其他部分一次读取
+const ARTICLE_BATCH_SIZE: i64 = 500;
+const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    // 首页、首页分页及关于页面
+    Pages,
+    Users,
+    Articles,
+}
+
+impl Section {
+    fn name(&self) -> &'static str {
+        match self {
+            Section::Pages => "pages",
+            Section::Users => "users",
+            Section::Articles => "articles",
+        }
+    }
+
+    fn batch_size(&self) -> i64 {
+        match self {
+            Section::Articles => ARTICLE_BATCH_SIZE,
+            _ => SITEMAP_MAX_URLS,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Section> {
+        match name {
+            "pages" => Some(Section::Pages),
+            "users" => Some(Section::Users),
+            "articles" => Some(Section::Articles),
+            _ => None,
+        }
+    }
+}
+
+struct SitemapUrl {
+    loc: String,
+    lastmod: Option<NaiveDateTime>,
+    images: Vec<String>,
+}
+
+// 旧地址，保留给已经提交过的搜索引擎
+pub async fn legacy_sitemap_handler() -> Response {
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, "/sitemap.xml")],
+    )
+        .into_response()
+}
+
+// /sitemap.xml，列出所有子sitemap
+pub async fn sitemap_index_handler(
     Extension(state): Extension<Arc<State>>,
-) -> Result<Html<String>, HttpRESTError> {
-    let conn = state
-        .pool
-        .get()
-        .await
-        .map_err(|err| OtherError::Unknown(err))?;
-
-    let query_result = conn
-        .query(
-            "select articles.pk, articles.update_time
-from articles
-order by update_time desc;",
-            &[],
-        )
-        .await
-        .map_err(|err| OtherError::Unknown(err))?;
-
-    let mut output = Cursor::new(Vec::new());
-    let mut writer = EmitterConfig::new()
-        .perform_indent(true)
-        .create_writer(&mut output);
-    writer
-        .write(
-            XmlEvent::start_element("urlset")
-                .attr("xmlns", "http://www.sitemaps.org/schemas/sitemap/0.9"),
-        )
-        .map_err(|err| OtherError::Unknown(err))?;
-    for row in query_result {
-        let pk: &str = row.get("pk");
-        let update_time: chrono::NaiveDateTime = row.get("update_time");
-        let update_time_utc: chrono::DateTime<Utc> = Utc.from_utc_datetime(&update_time);
-        let lastmod: String = update_time_utc.to_rfc3339();
-        writer
-            .write(XmlEvent::start_element("url"))
-            .map_err(|err| OtherError::Unknown(err))?;
-
-        writer
-            .write(XmlEvent::start_element("loc"))
-            .map_err(|err| OtherError::Unknown(err))?;
-        writer
-            .write(XmlEvent::characters(
-                format!("https://sfx.xyz/article/read/{}", pk).as_str(),
-            ))
-            .map_err(|err| OtherError::Unknown(err))?;
-        writer
-            .write(XmlEvent::end_element())
-            .map_err(|err| OtherError::Unknown(err))?;
-
-        writer
-            .write(XmlEvent::start_element("lastmod"))
-            .map_err(|err| OtherError::Unknown(err))?;
-        writer
-            .write(XmlEvent::characters(lastmod.as_str()))
-            .map_err(|err| OtherError::Unknown(err))?;
-        writer
-            .write(XmlEvent::end_element())
-            .map_err(|err| OtherError::Unknown(err))?;
-
-        writer
-            .write(XmlEvent::end_element())
-            .map_err(|err| OtherError::Unknown(err))?;
+) -> Result<Response, HttpRESTError> {
+    let site_url = state.config.current().site_url.clone();
+
+    let mut sitemaps: Vec<String> = Vec::new();
+    for section in [Section::Pages, Section::Users, Section::Articles] {
+        let count = section_count(&state, section).await?;
+        let pages = ((count + SITEMAP_MAX_URLS - 1) / SITEMAP_MAX_URLS).max(1);
+        for page in 1..=pages {
+            sitemaps.push(format!(
+                "{}/sitemap/{}-{}.xml",
+                site_url,
+                section.name(),
+                page
+            ));
+        }
+    }
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for loc in sitemaps {
+        xml.push_str(&format!(
+            "  <sitemap><loc>{}</loc></sitemap>\n",
+            escape_str_pcdata(&loc)
+        ));
+    }
+    xml.push_str("</sitemapindex>\n");
+
+    Ok(([(header::CONTENT_TYPE, XML_CONTENT_TYPE)], xml).into_response())
+}
+
+// /sitemap/:name，name形如articles-1.xml，内容边查询边输出
+pub async fn sitemap_handler(
+    Path(params): Path<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, HttpRESTError> {
+    let name = params.get("name").ok_or(AppError::InvalidParameter)?;
+    let (section, page) = name
+        .strip_suffix(".xml")
+        .and_then(|name| name.rsplit_once('-'))
+        .and_then(|(section, page)| Some((Section::parse(section)?, page.parse::<i64>().ok()?)))
+        .ok_or(AppError::NotFound)?;
+    let count = section_count(&state, section).await?;
+    if page < 1 || (page - 1) * SITEMAP_MAX_URLS >= count.max(1) {
+        return Err(AppError::NotFound.into());
+    }
+
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        let offset = (page - 1) * SITEMAP_MAX_URLS;
+        let end = (offset + SITEMAP_MAX_URLS).min(count);
+        let prologue = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset \
+xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n";
+        if sender.send_data(Bytes::from(prologue)).await.is_err() {
+            return;
+        }
+
+        let site_url = state.config.current().site_url.clone();
+        let mut position = offset;
+        // 文章从上一批的最后一篇之后继续读取，避免每批都使用offset
+        let mut after: Option<(NaiveDateTime, String)> = None;
+        while position < end {
+            let limit = section.batch_size().min(end - position);
+            let urls =
+                match section_urls(&state, &site_url, section, position, limit, &mut after).await {
+                    Ok(urls) => urls,
+                    Err(err) => {
+                        // 已经开始输出，只能中断响应
+                        tracing::error!("生成sitemap出错: {:?}", err);
+                        sender.abort();
+                        return;
+                    }
+                };
+            if urls.is_empty() {
+                break;
+            }
+            position += urls.len() as i64;
+            let chunk: String = urls.iter().map(write_url).collect();
+            if sender.send_data(Bytes::from(chunk)).await.is_err() {
+                return;
+            }
+        }
+        let _ = sender.send_data(Bytes::from("</urlset>\n")).await;
+    });
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, XML_CONTENT_TYPE)
+        .body(boxed(body))
+        .map_err(OtherError::Unknown)?)
+}
+
+async fn section_count(state: &Arc<State>, section: Section) -> Result<i64, AppError> {
+    match section {
+        Section::Pages => {
+            let page_size = state.config.current().index_page_size as i64;
+            let count = state.articles.count_published().await?;
+            // 首页分页及关于页面
+            Ok(((count + page_size - 1) / page_size).max(1) + 1)
+        }
+        Section::Users => Ok(state.articles.list_creators().await?.len() as i64),
+        Section::Articles => state.articles.count_published().await,
+    }
+}
+
+async fn section_urls(
+    state: &Arc<State>,
+    site_url: &str,
+    section: Section,
+    offset: i64,
+    limit: i64,
+    after: &mut Option<(NaiveDateTime, String)>,
+) -> Result<Vec<SitemapUrl>, AppError> {
+    let urls = match section {
+        Section::Pages => (offset..offset + limit)
+            .map(|index| SitemapUrl {
+                loc: match index {
+                    0 => format!("{}/about", site_url),
+                    1 => format!("{}/", site_url),
+                    _ => format!("{}/?p={}", site_url, index),
+                },
+                lastmod: None,
+                images: Vec::new(),
+            })
+            .collect(),
+        Section::Users => state
+            .articles
+            .list_creators()
+            .await?
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(pk, update_time)| SitemapUrl {
+                loc: format!("{}/user/{}", site_url, pk),
+                lastmod: Some(update_time),
+                images: Vec::new(),
+            })
+            .collect(),
+        Section::Articles => {
+            // 只有每页的第一批使用offset定位
+            let offset = if after.is_some() { 0 } else { offset };
+            let articles = state
+                .articles
+                .list_published_after(after.clone(), offset, limit)
+                .await?;
+            if let Some(last) = articles.last() {
+                *after = Some((last.create_time, last.pk.clone()));
+            }
+            articles
+                .into_iter()
+                .map(|article| SitemapUrl {
+                    loc: format!("{}{}", site_url, article.path()),
+                    lastmod: Some(article.update_time),
+                    images: article::image_urls(site_url, &article.body),
+                })
+                .collect()
+        }
+    };
+    Ok(urls)
+}
+
+fn write_url(url: &SitemapUrl) -> String {
+    let mut xml = format!("  <url>\n    <loc>{}</loc>\n", escape_str_pcdata(&url.loc));
+    if let Some(lastmod) = &url.lastmod {
+        xml.push_str(&format!(
+            "    <lastmod>{}</lastmod>\n",
+            Utc.from_utc_datetime(lastmod).to_rfc3339()
+        ));
+    }
+    for image in &url.images {
+        xml.push_str(&format!(
+            "    <image:image><image:loc>{}</image:loc></image:image>\n",
+            escape_str_pcdata(image)
+        ));
     }
-    writer
-        .write(XmlEvent::end_element())
-        .map_err(|err| OtherError::Unknown(err))?;
-    output.seek(SeekFrom::Start(0)).unwrap();
-    let mut result = String::new();
-    output
-        .read_to_string(&mut result)
-        .map_err(|err| OtherError::Unknown(err))?;
-
-    Ok(Html(result))
+    xml.push_str("  </url>\n");
+    xml
 }
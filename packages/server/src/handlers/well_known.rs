@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::Extension;
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{Duration, Utc};
+
+use crate::handlers::State;
+use crate::models::error::AppError;
+use crate::views::restful::error::HttpRESTError;
+
+const TEXT_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+fn text_response(content: String) -> Response {
+    ([(header::CONTENT_TYPE, TEXT_CONTENT_TYPE)], content).into_response()
+}
+
+pub async fn robots_handler(Extension(state): Extension<Arc<State>>) -> Response {
+    let config = state.config.current();
+    let mut lines: Vec<String> = vec!["User-agent: *".to_string()];
+    if config.allow_indexing {
+        lines.extend(
+            config
+                .robots_disallow
+                .iter()
+                .map(|path| format!("Disallow: {}", path)),
+        );
+    } else {
+        lines.push("Disallow: /".to_string());
+    }
+    lines.push("".to_string());
+    lines.push(format!("Sitemap: {}/sitemap.xml", config.site_url));
+    text_response(lines.join("\n") + "\n")
+}
+
+// https://www.rfc-editor.org/rfc/rfc9116
+pub async fn security_handler(
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Response, HttpRESTError> {
+    let config = state.config.current();
+    if config.security_contacts.is_empty() {
+        return Err(AppError::NotFound.into());
+    }
+    let mut lines: Vec<String> = config
+        .security_contacts
+        .iter()
+        .map(|contact| format!("Contact: {}", contact))
+        .collect();
+    let expires = Utc::now() + Duration::days(config.security_expires_days);
+    lines.push(format!(
+        "Expires: {}",
+        expires.format("%Y-%m-%dT%H:%M:%S.000Z")
+    ));
+    if !config.security_policy.is_empty() {
+        lines.push(format!("Policy: {}", config.security_policy));
+    }
+    lines.push("Preferred-Languages: zh, en".to_string());
+    lines.push(format!(
+        "Canonical: {}/.well-known/security.txt",
+        config.site_url
+    ));
+    Ok(text_response(lines.join("\n") + "\n"))
+}
+
+// https://humanstxt.org/
+pub async fn humans_handler(Extension(state): Extension<Arc<State>>) -> Response {
+    let config = state.config.current();
+    let mut content = String::from("/* TEAM */\n");
+    for member in &config.humans_team {
+        content.push_str(&format!("    {}\n", member));
+    }
+    content.push_str("\n/* SITE */\n");
+    content.push_str(&format!("    Name: {}\n", config.site_name));
+    content.push_str(&format!("    URL: {}\n", config.site_url));
+    content.push_str("    Language: Chinese\n");
+    content.push_str("    Software: Rust, axum, handlebars\n");
+    text_response(content)
+}
+
+// 不允许收录时，所有响应都带上X-Robots-Tag
+pub async fn robots_tag_layer(request: Request<Body>, next: Next<Body>) -> Response {
+    let allow_indexing = request
+        .extensions()
+        .get::<Arc<State>>()
+        .map_or(true, |state| state.config.current().allow_indexing);
+    let mut response = next.run(request).await;
+    if !allow_indexing {
+        response.headers_mut().insert(
+            "x-robots-tag",
+            HeaderValue::from_static("noindex, nofollow"),
+        );
+    }
+    response
+}
@@ -4,8 +4,10 @@ use handlebars::{
 };
 use string_builder::Builder;
 
-#[derive(Clone, Copy)]
-pub struct SimpleHelper;
+#[derive(Clone)]
+pub struct SimpleHelper {
+    pub res_url: String,
+}
 
 impl HelperDef for SimpleHelper {
     fn call<'reg: 'rc, 'rc>(
@@ -23,12 +25,7 @@ impl HelperDef for SimpleHelper {
             "".to_string()
         };
 
-        let res_url = if is_debug() {
-            "http://127.0.0.1:3000"
-        } else {
-            "https://res.sfx.xyz"
-        };
-        out.write(res_url)?;
+        out.write(self.res_url.as_str())?;
         if is_debug() || release_path.is_empty() {
             out.write(param.value().render().as_ref())?;
         } else {
@@ -9,6 +9,16 @@ use tokio_postgres::NoTls;
 
 pub type ConnectionPool = Pool<PostgresConnectionManager<NoTls>>;
 
+pub async fn connect(dsn: &str) -> Result<ConnectionPool, AppError> {
+    let manager =
+        PostgresConnectionManager::new_from_stringlike(dsn, NoTls).map_err(AppError::Postgresql)?;
+    let pool = Pool::builder()
+        .build(manager)
+        .await
+        .map_err(AppError::Postgresql)?;
+    Ok(pool)
+}
+
 struct DatabaseConnection(PooledConnection<'static, PostgresConnectionManager<NoTls>>);
 
 use crate::models::error::{AppError, OtherError};
@@ -25,12 +35,9 @@ where
     async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
         let Extension(pool) = Extension::<ConnectionPool>::from_request(req)
             .await
-            .map_err(|err| OtherError::Unknown(err))?;
+            .map_err(OtherError::Unknown)?;
 
-        let conn = pool
-            .get_owned()
-            .await
-            .map_err(|err| OtherError::Unknown(err))?;
+        let conn = pool.get_owned().await.map_err(OtherError::Unknown)?;
 
         Ok(Self(conn))
     }
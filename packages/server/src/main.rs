@@ -1,33 +1,23 @@
-use crate::utils::env::read_env;
-use foo_rs::{testcall, testcall_cpp};
-use std::env;
-use std::net::SocketAddr;
+use crate::cli::Cli;
+use clap::Parser;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod cli;
 mod config;
 mod handlers;
 mod helpers;
 mod layers;
+mod migrations;
 mod models;
+mod repository;
 mod service;
 mod utils;
 mod views;
 
 #[tokio::main]
 async fn main() {
-    println!("Hello, world from Rust!");
-    let args: Vec<String> = env::args().collect();
-    println!("{:?}", args);
-    let port = read_env::<u16>("PORT").unwrap_or(8080);
-    println!("port: {:?}", port);
+    let cli = Cli::parse();
 
-    // calling the function from foo library
-    unsafe {
-        testcall(3.14159);
-    };
-    unsafe {
-        testcall_cpp(3.14159);
-    };
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "debug".into()),
@@ -35,11 +25,8 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    tracing::debug!("listening on {}", addr);
-
-    axum::Server::bind(&addr)
-        .serve(handlers::app().await.into_make_service())
-        .await
-        .unwrap();
+    if let Err(err) = cli.run().await {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
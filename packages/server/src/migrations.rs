@@ -0,0 +1,185 @@
+use chrono::Utc;
+
+use crate::layers::ConnectionPool;
+use crate::models::error::{AppError, OtherError};
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+// 0001会接管已存在的表，回滚不能低于这个版本，否则会删掉原有数据
+const BASELINE_VERSION: i64 = 1;
+
+// 新的迁移追加在末尾，版本号必须递增，已发布的迁移不要再修改
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "init",
+        up: include_str!("../migrations/0001_init.up.sql"),
+        down: include_str!("../migrations/0001_init.down.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "articles_soft_delete",
+        up: include_str!("../migrations/0002_articles_soft_delete.up.sql"),
+        down: include_str!("../migrations/0002_articles_soft_delete.down.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "articles_revisions",
+        up: include_str!("../migrations/0003_articles_revisions.up.sql"),
+        down: include_str!("../migrations/0003_articles_revisions.down.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "articles_version",
+        up: include_str!("../migrations/0004_articles_version.up.sql"),
+        down: include_str!("../migrations/0004_articles_version.down.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "articles_workflow",
+        up: include_str!("../migrations/0005_articles_workflow.up.sql"),
+        down: include_str!("../migrations/0005_articles_workflow.down.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "articles_reading",
+        up: include_str!("../migrations/0006_articles_reading.up.sql"),
+        down: include_str!("../migrations/0006_articles_reading.down.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "articles_slug",
+        up: include_str!("../migrations/0007_articles_slug.up.sql"),
+        down: include_str!("../migrations/0007_articles_slug.down.sql"),
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied_at: Option<chrono::NaiveDateTime>,
+}
+
+pub struct Migrator {
+    pool: ConnectionPool,
+}
+
+impl Migrator {
+    pub fn new(pool: ConnectionPool) -> Migrator {
+        Migrator { pool }
+    }
+
+    async fn ensure_version_table(&self) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+        conn.batch_execute(
+            "create table if not exists schema_migrations
+(
+    version    bigint primary key,
+    name       text      not null,
+    applied_at timestamp not null
+);",
+        )
+        .await
+        .map_err(AppError::Postgresql)?;
+        Ok(())
+    }
+
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>, AppError> {
+        self.ensure_version_table().await?;
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+        let query_result = conn
+            .query("select version, applied_at from schema_migrations;", &[])
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        let mut result: Vec<MigrationStatus> = Vec::new();
+        for migration in MIGRATIONS {
+            let applied_at = query_result
+                .iter()
+                .find(|row| row.get::<_, i64>("version") == migration.version)
+                .map(|row| row.get::<_, chrono::NaiveDateTime>("applied_at"));
+            result.push(MigrationStatus {
+                version: migration.version,
+                name: migration.name,
+                applied_at,
+            });
+        }
+        Ok(result)
+    }
+
+    // 按顺序执行所有未执行的迁移，返回本次执行的版本
+    pub async fn migrate(&self) -> Result<Vec<i64>, AppError> {
+        let mut applied: Vec<i64> = Vec::new();
+        for status in self.status().await? {
+            if status.applied_at.is_some() {
+                continue;
+            }
+            let migration = find_migration(status.version)?;
+            let mut conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+            let transaction = conn.transaction().await.map_err(AppError::Postgresql)?;
+            transaction
+                .batch_execute(migration.up)
+                .await
+                .map_err(AppError::Postgresql)?;
+            let naive_date_time = Utc::now().naive_utc();
+            transaction
+                .execute(
+                    "insert into schema_migrations(version, name, applied_at) values($1, $2, $3);",
+                    &[&migration.version, &migration.name, &naive_date_time],
+                )
+                .await
+                .map_err(AppError::Postgresql)?;
+            transaction.commit().await.map_err(AppError::Postgresql)?;
+            tracing::info!("已执行迁移 {:04}_{}", migration.version, migration.name);
+            applied.push(migration.version);
+        }
+        Ok(applied)
+    }
+
+    // 从最新的版本开始回滚指定数量的迁移
+    pub async fn rollback(&self, steps: usize) -> Result<Vec<i64>, AppError> {
+        let mut rolled_back: Vec<i64> = Vec::new();
+        let mut statuses = self.status().await?;
+        statuses.retain(|status| status.applied_at.is_some());
+        let targets: Vec<&MigrationStatus> = statuses.iter().rev().take(steps).collect();
+        if targets
+            .iter()
+            .any(|status| status.version <= BASELINE_VERSION)
+        {
+            return Err(AppError::IrreversibleMigration(BASELINE_VERSION));
+        }
+        for status in targets {
+            let migration = find_migration(status.version)?;
+            let mut conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+            let transaction = conn.transaction().await.map_err(AppError::Postgresql)?;
+            transaction
+                .batch_execute(migration.down)
+                .await
+                .map_err(AppError::Postgresql)?;
+            transaction
+                .execute(
+                    "delete from schema_migrations where version = $1;",
+                    &[&migration.version],
+                )
+                .await
+                .map_err(AppError::Postgresql)?;
+            transaction.commit().await.map_err(AppError::Postgresql)?;
+            tracing::info!("已回滚迁移 {:04}_{}", migration.version, migration.name);
+            rolled_back.push(migration.version);
+        }
+        Ok(rolled_back)
+    }
+}
+
+fn find_migration(version: i64) -> Result<&'static Migration, AppError> {
+    MIGRATIONS
+        .iter()
+        .find(|migration| migration.version == version)
+        .ok_or(AppError::NotFound)
+}
@@ -1,5 +1,7 @@
+pub(crate) mod account;
 pub(crate) mod article;
 pub(crate) mod claims;
 pub(crate) mod error;
-pub(crate) mod jwt;
 pub(crate) mod index;
+pub(crate) mod jwt;
+pub(crate) mod revision;
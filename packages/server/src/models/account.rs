@@ -0,0 +1,41 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Enum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountRole {
+    // 只能提交自己的文章进入审核
+    Author,
+    // 可以审核、发布、定时发布及归档任意文章
+    Editor,
+}
+
+impl AccountRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountRole::Author => "author",
+            AccountRole::Editor => "editor",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<AccountRole> {
+        match value {
+            "author" => Some(AccountRole::Author),
+            "editor" => Some(AccountRole::Editor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountModel {
+    pub pk: String,
+    pub uname: String,
+    pub nickname: String,
+    pub email: String,
+    pub description: String,
+    pub photo: String,
+    pub site: String,
+    pub role: AccountRole,
+    pub create_time: chrono::NaiveDateTime,
+}
@@ -1,5 +1,184 @@
-#[derive(Debug, Clone)]
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::utils::reading::ReadingStats;
+
+// 数值与articles.status列保持一致，0和1沿用旧的草稿与发布
+#[derive(Enum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(i32)]
+pub enum ArticleStatus {
+    Draft = 0,
+    Published = 1,
+    Review = 2,
+    Scheduled = 3,
+    Archived = 4,
+}
+
+impl ArticleStatus {
+    pub fn from_i32(value: i32) -> Option<ArticleStatus> {
+        match value {
+            0 => Some(ArticleStatus::Draft),
+            1 => Some(ArticleStatus::Published),
+            2 => Some(ArticleStatus::Review),
+            3 => Some(ArticleStatus::Scheduled),
+            4 => Some(ArticleStatus::Archived),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArticleStatus::Draft => "draft",
+            ArticleStatus::Published => "published",
+            ArticleStatus::Review => "review",
+            ArticleStatus::Scheduled => "scheduled",
+            ArticleStatus::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ArticleStatus> {
+        [
+            ArticleStatus::Draft,
+            ArticleStatus::Published,
+            ArticleStatus::Review,
+            ArticleStatus::Scheduled,
+            ArticleStatus::Archived,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == value)
+    }
+
+    // 出现在首页、sitemap等列表中
+    pub fn is_listed(&self) -> bool {
+        *self == ArticleStatus::Published
+    }
+
+    // 可以通过链接直接访问，归档的文章不再列出但链接仍然有效
+    pub fn is_readable(&self) -> bool {
+        matches!(self, ArticleStatus::Published | ArticleStatus::Archived)
+    }
+
+    pub fn can_transition_to(&self, to: ArticleStatus) -> bool {
+        use ArticleStatus::*;
+        matches!(
+            (*self, to),
+            (Draft, Review)
+                | (Draft, Scheduled)
+                | (Draft, Published)
+                | (Review, Draft)
+                | (Review, Scheduled)
+                | (Review, Published)
+                | (Scheduled, Draft)
+                | (Scheduled, Published)
+                | (Published, Draft)
+                | (Published, Archived)
+                | (Archived, Draft)
+                | (Archived, Published)
+        )
+    }
+}
+
+impl Display for ArticleStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// 客户端提交的正文格式，统一转换为文档模型保存
+#[derive(Enum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DocumentFormat {
+    Markdown,
+    Document,
+}
+
+impl Default for DocumentFormat {
+    fn default() -> Self {
+        DocumentFormat::Document
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ArticleModel {
     pub pk: String,
     pub title: String,
+    pub body: serde_json::Value,
+    pub description: String,
+    pub keywords: String,
+    pub status: ArticleStatus,
+    // 定时发布的时间，仅在Scheduled状态下有效
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub creator: String,
+    pub creator_nickname: String,
+    pub create_time: chrono::NaiveDateTime,
+    pub update_time: chrono::NaiveDateTime,
+    pub delete_time: Option<chrono::NaiveDateTime>,
+    pub views: i64,
+    // 每次修改加1，用于乐观锁及ETag
+    pub version: i64,
+    // 根据标题生成，用于文章地址；添加slug之前的文章在下次保存前为空
+    pub slug: String,
+    // 保存时根据正文计算
+    pub word_count: i32,
+    pub reading_minutes: i32,
+    pub excerpt: String,
+}
+
+impl ArticleModel {
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.version)
+    }
+
+    // 站内的规范地址，还没有slug的文章使用pk
+    pub fn path(&self) -> String {
+        if self.slug.is_empty() {
+            format!("/article/read/{}", self.pk)
+        } else {
+            format!("/article/{}", self.slug)
+        }
+    }
+
+    // 关键词以逗号分隔，兼容中文逗号
+    pub fn keyword_list(&self) -> Vec<String> {
+        self.keywords
+            .split(|c| c == ',' || c == '，')
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect()
+    }
+
+    pub fn refresh_reading(&mut self) {
+        let stats = ReadingStats::from_document(&self.body);
+        self.word_count = stats.word_count;
+        self.reading_minutes = stats.reading_minutes;
+        self.excerpt = stats.excerpt;
+    }
+
+    // 添加这些字段之前保存的文章没有统计数据，读取时再计算
+    pub fn reading_stats(&self) -> ReadingStats {
+        if self.word_count > 0 {
+            return ReadingStats {
+                word_count: self.word_count,
+                reading_minutes: self.reading_minutes,
+                excerpt: self.excerpt.clone(),
+            };
+        }
+        ReadingStats::from_document(&self.body)
+    }
+
+    // 作者未填写描述时使用自动生成的摘要
+    pub fn summary(&self) -> String {
+        if self.description.trim().is_empty() {
+            self.reading_stats().excerpt
+        } else {
+            self.description.clone()
+        }
+    }
+
+    // 未删除且状态允许直接访问
+    pub fn is_readable(&self) -> bool {
+        self.delete_time.is_none() && self.status.is_readable()
+    }
 }
@@ -6,9 +6,11 @@ use axum::extract::{FromRequest, RequestParts};
 use axum::headers::authorization::Bearer;
 use axum::headers::Authorization;
 use axum::{Extension, TypedHeader};
-use jsonwebtoken::{decode, DecodingKey, EncodingKey, Validation};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::config::ProximaConfig;
 use crate::handlers::State;
 use crate::models::error::{AppError, OtherError};
 use crate::views::restful::error::HttpRESTError;
@@ -27,6 +29,30 @@ impl Keys {
     }
 }
 
+// 依次尝试当前密钥和轮换宽限期内的旧密钥
+pub fn decode_token<T: DeserializeOwned>(
+    state: &State,
+    token: &str,
+) -> Result<TokenData<T>, jsonwebtoken::errors::Error> {
+    let mut result = Err(jsonwebtoken::errors::ErrorKind::InvalidSignature.into());
+    for secret in state.config.jwt_secrets() {
+        let jwt_keys = Keys::new(secret.as_bytes());
+        result = decode::<T>(token, &jwt_keys.decoding, &Validation::default());
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+pub fn issue_token(config: &ProximaConfig, user: String, exp: usize) -> Result<String, AppError> {
+    let claims = Claims { exp, user };
+    let jwt_keys = Keys::new(config.jwt_secret.as_bytes());
+    let token =
+        encode(&Header::default(), &claims, &jwt_keys.encoding).map_err(OtherError::Unknown)?;
+    Ok(token)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Claims {
     // pub(crate) sub: String,
@@ -68,18 +94,16 @@ where
         let TypedHeader(Authorization(bearer)) =
             TypedHeader::<Authorization<Bearer>>::from_request(req)
                 .await
-                .map_err(|err| OtherError::Unknown(err))?;
+                .map_err(OtherError::Unknown)?;
         // Decode the user data
         type Extractors = (Extension<Arc<State>>);
 
         let (Extension(state)) = Extractors::from_request(req)
             .await
-            .map_err(|err| OtherError::Unknown(err))?;
+            .map_err(OtherError::Unknown)?;
 
-        let jwt_keys = Keys::new(&state.config.jwt_secret.as_bytes());
         let token_data =
-            decode::<Claims>(bearer.token(), &jwt_keys.decoding, &Validation::default())
-                .map_err(|err| OtherError::Unknown(err))?;
+            decode_token::<Claims>(&state, bearer.token()).map_err(OtherError::Unknown)?;
 
         Ok(token_data.claims)
     }
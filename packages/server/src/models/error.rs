@@ -1,8 +1,11 @@
+use async_graphql::ErrorExtensions;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use chrono::format::format;
 use serde::{Deserialize, Serialize};
+
+use crate::models::article::ArticleStatus;
 use serde_json::json;
 use std::error;
 use std::fmt::{Debug, Display, Formatter};
@@ -15,8 +18,22 @@ pub enum AppError {
     InvalidToken,
     InvalidParameter,
     NotFound,
+    Forbidden,
+    // 乐观锁冲突，附带服务端当前的版本
+    Conflict {
+        current_version: i64,
+    },
+    InvalidTransition {
+        from: ArticleStatus,
+        to: ArticleStatus,
+    },
+    // 保存时slug已被其他文章使用
+    SlugTaken,
+    // 回滚会越过不可回滚的迁移
+    IrreversibleMigration(i64),
     EmptyData,
     InvalidConfig(&'static str),
+    ConfigReport(Vec<String>),
     Graphql(async_graphql::Error),
     Postgresql(tokio_postgres::Error),
     Handlebars(handlebars::RenderError),
@@ -32,11 +49,41 @@ impl Display for AppError {
 impl Debug for AppError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            WrongCredentials => write!(f, "授权错误2"),
+            AppError::NotFound => write!(f, "未找到"),
+            AppError::Forbidden => write!(f, "无权操作"),
+            AppError::Conflict { current_version } => {
+                write!(f, "文章已被修改，当前版本为{}", current_version)
+            }
+            AppError::InvalidTransition { from, to } => {
+                write!(f, "文章状态不能从{}变更为{}", from, to)
+            }
+            AppError::SlugTaken => write!(f, "文章地址已被使用"),
+            AppError::IrreversibleMigration(version) => {
+                write!(f, "迁移{:04}不可回滚", version)
+            }
+            AppError::InvalidConfig(message) => write!(f, "配置有误: {}", message),
+            AppError::ConfigReport(errors) => write!(f, "配置有误:\n{}", errors.join("\n")),
+            _ => write!(f, "授权错误2"),
         }
     }
 }
 
+impl ErrorExtensions for AppError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| match self {
+            AppError::NotFound => e.set("code", "NOT_FOUND"),
+            AppError::Forbidden => e.set("code", "FORBIDDEN"),
+            AppError::InvalidTransition { .. } => e.set("code", "INVALID_TRANSITION"),
+            AppError::SlugTaken => e.set("code", "SLUG_TAKEN"),
+            AppError::Conflict { current_version } => {
+                e.set("code", "CONFLICT");
+                e.set("currentVersion", *current_version);
+            }
+            _ => {}
+        })
+    }
+}
+
 impl<T> From<OtherError<T>> for AppError
 where
     T: Debug,
@@ -1,9 +1,12 @@
-
 use serde::{Deserialize, Serialize};
 
+use crate::models::article::ArticleModel;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IndexModel {
     pub pk: String,
+    // 文章的站内地址
+    pub path: String,
     pub title: String,
     pub body: serde_json::Value,
     pub creator: String,
@@ -12,4 +15,28 @@ pub struct IndexModel {
     pub update_time_formatted: String,
     pub creator_nickname: String,
     pub views: i64,
+    pub word_count: i32,
+    pub reading_minutes: i32,
+    pub excerpt: String,
+}
+
+impl From<ArticleModel> for IndexModel {
+    fn from(model: ArticleModel) -> Self {
+        let reading = model.reading_stats();
+        IndexModel {
+            path: model.path(),
+            pk: model.pk,
+            title: model.title,
+            body: model.body,
+            creator: model.creator,
+            keywords: model.keywords,
+            description: model.description,
+            update_time_formatted: model.update_time.format("%Y年%m月%d日 %H:%M").to_string(),
+            creator_nickname: model.creator_nickname,
+            views: model.views,
+            word_count: reading.word_count,
+            reading_minutes: reading.reading_minutes,
+            excerpt: reading.excerpt,
+        }
+    }
 }
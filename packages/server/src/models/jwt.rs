@@ -10,7 +10,7 @@ use jsonwebtoken::{decode, Validation};
 use serde::{Deserialize, Serialize};
 
 use crate::handlers::State;
-use crate::models::claims::{Claims, Keys};
+use crate::models::claims::{decode_token, Claims, Keys};
 use crate::models::error::{AppError, OtherError};
 use crate::views::restful::error::HttpRESTError;
 
@@ -33,18 +33,16 @@ where
         let TypedHeader(Authorization(bearer)) =
             TypedHeader::<Authorization<Bearer>>::from_request(req)
                 .await
-                .map_err(|err| OtherError::Unknown(err))?;
+                .map_err(OtherError::Unknown)?;
         // Decode the user data
         type Extractors = (Extension<Arc<State>>);
 
         let (Extension(state)) = Extractors::from_request(req)
             .await
-            .map_err(|err| OtherError::Unknown(err))?;
+            .map_err(OtherError::Unknown)?;
 
-        let jwt_keys = Keys::new(&state.config.jwt_secret.as_bytes());
         let token_data =
-            decode::<Protected>(bearer.token(), &jwt_keys.decoding, &Validation::default())
-                .map_err(|err| OtherError::Unknown(err))?;
+            decode_token::<Protected>(&state, bearer.token()).map_err(OtherError::Unknown)?;
 
         Ok(token_data.claims)
     }
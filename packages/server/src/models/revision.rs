@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevisionModel {
+    pub pk: String,
+    pub article: String,
+    pub title: String,
+    pub body: serde_json::Value,
+    pub description: String,
+    pub keywords: String,
+    pub author: String,
+    pub create_time: chrono::NaiveDateTime,
+}
@@ -0,0 +1,86 @@
+pub(crate) mod memory;
+pub(crate) mod postgres;
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use crate::models::account::AccountModel;
+use crate::models::article::ArticleModel;
+use crate::models::error::AppError;
+use crate::models::revision::RevisionModel;
+
+#[async_trait]
+pub trait ArticleRepository: Debug + Send + Sync {
+    // 已发布的文章，按更新时间倒序
+    async fn list_published(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError>;
+
+    // 指定作者或关键词的已发布文章，按更新时间倒序，用于生成订阅源
+    async fn list_published_by(
+        &self,
+        creator: Option<&str>,
+        keyword: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError>;
+
+    async fn count_published(&self) -> Result<i64, AppError>;
+
+    // 已发布的文章，按(create_time, pk)排序，跳过after及之前的文章后再跳过offset篇，
+    // 排序键不随修改变化，用于sitemap的键集分页
+    async fn list_published_after(
+        &self,
+        after: Option<(chrono::NaiveDateTime, String)>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError>;
+
+    // 所有状态的文章，包括已删除的，按更新时间倒序，用于命令行管理
+    async fn list_all(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError>;
+
+    // 包括已删除的文章，调用方需要自行检查delete_time
+    async fn find(&self, pk: &str) -> Result<Option<ArticleModel>, AppError>;
+
+    // 按当前slug查找，包括已删除的文章
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<ArticleModel>, AppError>;
+
+    // 旧slug对应的文章pk
+    async fn find_slug_history(&self, slug: &str) -> Result<Option<String>, AppError>;
+
+    // 记录被替换的旧slug，已存在时忽略
+    async fn insert_slug_history(
+        &self,
+        slug: &str,
+        article: &str,
+        create_time: chrono::NaiveDateTime,
+    ) -> Result<(), AppError>;
+
+    // 有已发布文章的作者及其文章的最近更新时间，用于生成sitemap
+    async fn list_creators(&self) -> Result<Vec<(String, chrono::NaiveDateTime)>, AppError>;
+
+    async fn insert(&self, model: &ArticleModel) -> Result<(), AppError>;
+
+    // model.version为修改前的版本，与当前版本不一致时返回AppError::Conflict
+    async fn update(&self, model: &ArticleModel) -> Result<(), AppError>;
+
+    // 将publish_at不晚于now的定时文章改为已发布，返回被发布的文章pk
+    async fn publish_due(&self, now: chrono::NaiveDateTime) -> Result<Vec<String>, AppError>;
+}
+
+#[async_trait]
+pub trait AccountRepository: Debug + Send + Sync {
+    async fn insert(&self, model: &AccountModel) -> Result<(), AppError>;
+
+    async fn find(&self, pk: &str) -> Result<Option<AccountModel>, AppError>;
+
+    async fn find_by_uname(&self, uname: &str) -> Result<Option<AccountModel>, AppError>;
+}
+
+#[async_trait]
+pub trait RevisionRepository: Debug + Send + Sync {
+    async fn insert(&self, model: &RevisionModel) -> Result<(), AppError>;
+
+    // 按创建时间倒序
+    async fn list(&self, article: &str) -> Result<Vec<RevisionModel>, AppError>;
+
+    async fn find(&self, pk: &str) -> Result<Option<RevisionModel>, AppError>;
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::models::account::AccountModel;
+use crate::models::article::{ArticleModel, ArticleStatus};
+use crate::models::error::AppError;
+use crate::models::revision::RevisionModel;
+use crate::repository::{AccountRepository, ArticleRepository, RevisionRepository};
+
+/// 基于内存的实现，不需要数据库即可运行整个router，主要用于测试
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    articles: RwLock<HashMap<String, ArticleModel>>,
+    accounts: RwLock<HashMap<String, AccountModel>>,
+    revisions: RwLock<Vec<RevisionModel>>,
+    // 旧slug到文章pk
+    slugs: RwLock<HashMap<String, String>>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> MemoryRepository {
+        MemoryRepository::default()
+    }
+
+    // 与postgres实现中left join accounts的行为保持一致
+    fn with_nickname(&self, mut model: ArticleModel) -> ArticleModel {
+        model.creator_nickname = self
+            .accounts
+            .read()
+            .unwrap()
+            .get(&model.creator)
+            .map_or("".to_string(), |account| account.nickname.clone());
+        model
+    }
+
+    fn published(&self) -> Vec<ArticleModel> {
+        let mut models: Vec<ArticleModel> = self
+            .articles
+            .read()
+            .unwrap()
+            .values()
+            .filter(|model| model.status.is_listed() && model.delete_time.is_none())
+            .cloned()
+            .collect();
+        models.sort_by(|a, b| b.update_time.cmp(&a.update_time));
+        models
+    }
+}
+
+// 与数据库中slug的唯一约束一致
+fn slug_taken(articles: &HashMap<String, ArticleModel>, model: &ArticleModel) -> bool {
+    !model.slug.is_empty()
+        && articles
+            .values()
+            .any(|other| other.pk != model.pk && other.slug == model.slug)
+}
+
+#[async_trait]
+impl ArticleRepository for MemoryRepository {
+    async fn list_published(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError> {
+        Ok(self
+            .published()
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(|model| self.with_nickname(model))
+            .collect())
+    }
+
+    async fn list_published_after(
+        &self,
+        after: Option<(chrono::NaiveDateTime, String)>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError> {
+        let mut models = self.published();
+        models.sort_by(|a, b| (a.create_time, &a.pk).cmp(&(b.create_time, &b.pk)));
+        Ok(models
+            .into_iter()
+            .filter(|model| {
+                after.as_ref().map_or(true, |(create_time, pk)| {
+                    (model.create_time, &model.pk) > (*create_time, pk)
+                })
+            })
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(|model| self.with_nickname(model))
+            .collect())
+    }
+
+    async fn list_all(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError> {
+        let mut models: Vec<ArticleModel> =
+            self.articles.read().unwrap().values().cloned().collect();
+        models.sort_by(|a, b| {
+            b.update_time
+                .cmp(&a.update_time)
+                .then_with(|| a.pk.cmp(&b.pk))
+        });
+        Ok(models
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(|model| self.with_nickname(model))
+            .collect())
+    }
+
+    async fn list_published_by(
+        &self,
+        creator: Option<&str>,
+        keyword: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError> {
+        Ok(self
+            .published()
+            .into_iter()
+            .filter(|model| creator.map_or(true, |creator| model.creator == creator))
+            .filter(|model| {
+                keyword.map_or(true, |keyword| {
+                    model.keyword_list().iter().any(|item| item == keyword)
+                })
+            })
+            .take(limit.max(0) as usize)
+            .map(|model| self.with_nickname(model))
+            .collect())
+    }
+
+    async fn count_published(&self) -> Result<i64, AppError> {
+        Ok(self.published().len() as i64)
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<ArticleModel>, AppError> {
+        let model = self.articles.read().unwrap().get(pk).cloned();
+        Ok(model.map(|model| self.with_nickname(model)))
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<ArticleModel>, AppError> {
+        let model = self
+            .articles
+            .read()
+            .unwrap()
+            .values()
+            .find(|model| !model.slug.is_empty() && model.slug == slug)
+            .cloned();
+        Ok(model.map(|model| self.with_nickname(model)))
+    }
+
+    async fn find_slug_history(&self, slug: &str) -> Result<Option<String>, AppError> {
+        Ok(self.slugs.read().unwrap().get(slug).cloned())
+    }
+
+    async fn insert_slug_history(
+        &self,
+        slug: &str,
+        article: &str,
+        _create_time: chrono::NaiveDateTime,
+    ) -> Result<(), AppError> {
+        self.slugs
+            .write()
+            .unwrap()
+            .entry(slug.to_string())
+            .or_insert_with(|| article.to_string());
+        Ok(())
+    }
+
+    async fn list_creators(&self) -> Result<Vec<(String, chrono::NaiveDateTime)>, AppError> {
+        let mut creators: HashMap<String, chrono::NaiveDateTime> = HashMap::new();
+        for model in self.published() {
+            let update_time = creators.entry(model.creator).or_insert(model.update_time);
+            *update_time = (*update_time).max(model.update_time);
+        }
+        let mut result: Vec<(String, chrono::NaiveDateTime)> = creators.into_iter().collect();
+        result.sort_by(|a, b| b.1.cmp(&a.1));
+        Ok(result)
+    }
+
+    async fn insert(&self, model: &ArticleModel) -> Result<(), AppError> {
+        let mut articles = self.articles.write().unwrap();
+        if articles.contains_key(&model.pk) {
+            return Err(AppError::InvalidData);
+        }
+        if slug_taken(&articles, model) {
+            return Err(AppError::SlugTaken);
+        }
+        articles.insert(model.pk.clone(), model.clone());
+        Ok(())
+    }
+
+    async fn update(&self, model: &ArticleModel) -> Result<(), AppError> {
+        let mut articles = self.articles.write().unwrap();
+        if slug_taken(&articles, model) {
+            return Err(AppError::SlugTaken);
+        }
+        let current = articles.get_mut(&model.pk).ok_or(AppError::NotFound)?;
+        if current.version != model.version {
+            return Err(AppError::Conflict {
+                current_version: current.version,
+            });
+        }
+        *current = model.clone();
+        current.version += 1;
+        Ok(())
+    }
+
+    async fn publish_due(&self, now: chrono::NaiveDateTime) -> Result<Vec<String>, AppError> {
+        let mut published: Vec<String> = Vec::new();
+        for model in self.articles.write().unwrap().values_mut() {
+            let due = model
+                .publish_at
+                .map_or(false, |publish_at| publish_at <= now);
+            if model.status == ArticleStatus::Scheduled && due && model.delete_time.is_none() {
+                model.status = ArticleStatus::Published;
+                model.update_time = now;
+                model.version += 1;
+                published.push(model.pk.clone());
+            }
+        }
+        Ok(published)
+    }
+}
+
+#[async_trait]
+impl AccountRepository for MemoryRepository {
+    async fn insert(&self, model: &AccountModel) -> Result<(), AppError> {
+        let mut accounts = self.accounts.write().unwrap();
+        if accounts
+            .values()
+            .any(|account| account.uname == model.uname)
+        {
+            return Err(AppError::InvalidData);
+        }
+        accounts.insert(model.pk.clone(), model.clone());
+        Ok(())
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<AccountModel>, AppError> {
+        Ok(self.accounts.read().unwrap().get(pk).cloned())
+    }
+
+    async fn find_by_uname(&self, uname: &str) -> Result<Option<AccountModel>, AppError> {
+        Ok(self
+            .accounts
+            .read()
+            .unwrap()
+            .values()
+            .find(|account| account.uname == uname)
+            .cloned())
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for MemoryRepository {
+    async fn insert(&self, model: &RevisionModel) -> Result<(), AppError> {
+        self.revisions.write().unwrap().push(model.clone());
+        Ok(())
+    }
+
+    async fn list(&self, article: &str) -> Result<Vec<RevisionModel>, AppError> {
+        let mut result: Vec<RevisionModel> = self
+            .revisions
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|model| model.article == article)
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| b.create_time.cmp(&a.create_time));
+        Ok(result)
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<RevisionModel>, AppError> {
+        Ok(self
+            .revisions
+            .read()
+            .unwrap()
+            .iter()
+            .find(|model| model.pk == pk)
+            .cloned())
+    }
+}
@@ -0,0 +1,511 @@
+use async_trait::async_trait;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::Row;
+
+use crate::layers::ConnectionPool;
+use crate::models::account::{AccountModel, AccountRole};
+use crate::models::article::{ArticleModel, ArticleStatus};
+use crate::models::error::{AppError, OtherError};
+use crate::models::revision::RevisionModel;
+use crate::repository::{AccountRepository, ArticleRepository, RevisionRepository};
+
+const ARTICLE_SELECT: &str = "select articles.pk, articles.title, articles.body,
+articles.description, articles.keywords, articles.status, articles.publish_at, articles.creator,
+articles.create_time, articles.update_time, articles.delete_time, articles.version,
+articles.word_count, articles.reading_minutes, articles.excerpt, articles.slug, accounts.nickname, articles_views.views
+from articles
+    left join accounts on articles.creator = accounts.pk
+	left join articles_views on articles.pk = articles_views.pk";
+
+// 0007_articles_slug中slug列unique约束的默认名称
+const ARTICLES_SLUG_CONSTRAINT: &str = "articles_slug_key";
+
+const ACCOUNT_SELECT: &str =
+    "select accounts.pk, accounts.uname, accounts.nickname, accounts.email,
+accounts.description, accounts.photo, accounts.site, accounts.role, accounts.create_time
+from accounts";
+
+const REVISION_SELECT: &str = "select articles_revisions.pk, articles_revisions.article,
+articles_revisions.title, articles_revisions.body, articles_revisions.description,
+articles_revisions.keywords, articles_revisions.author, articles_revisions.create_time
+from articles_revisions";
+
+#[derive(Debug, Clone)]
+pub struct PostgresRepository {
+    pool: ConnectionPool,
+}
+
+impl PostgresRepository {
+    pub fn new(pool: ConnectionPool) -> PostgresRepository {
+        PostgresRepository { pool }
+    }
+}
+
+// slug有唯一约束，空字符串存为null
+fn empty_as_null(value: &str) -> Option<&str> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// slug的唯一约束冲突单独区分，由调用方重新生成slug
+fn article_write_error(err: tokio_postgres::Error) -> AppError {
+    let slug_taken = err.code() == Some(&SqlState::UNIQUE_VIOLATION)
+        && err.as_db_error().and_then(|db_error| db_error.constraint())
+            == Some(ARTICLES_SLUG_CONSTRAINT);
+    if slug_taken {
+        AppError::SlugTaken
+    } else {
+        AppError::Postgresql(err)
+    }
+}
+
+fn article_from_row(row: &Row) -> ArticleModel {
+    let description: Option<&str> = row.get("description");
+    let keywords: Option<&str> = row.get("keywords");
+    let creator_nickname: Option<&str> = row.get("nickname");
+    let views: Option<i64> = row.get("views");
+    let slug: Option<&str> = row.get("slug");
+
+    ArticleModel {
+        pk: row.get("pk"),
+        title: row.get("title"),
+        body: row.get("body"),
+        description: description.unwrap_or("").to_string(),
+        keywords: keywords.unwrap_or("").to_string(),
+        status: ArticleStatus::from_i32(row.get("status")).unwrap_or(ArticleStatus::Draft),
+        publish_at: row.get("publish_at"),
+        creator: row.get("creator"),
+        creator_nickname: creator_nickname.unwrap_or("").to_string(),
+        create_time: row.get("create_time"),
+        update_time: row.get("update_time"),
+        delete_time: row.get("delete_time"),
+        views: views.unwrap_or(0),
+        version: row.get("version"),
+        slug: slug.unwrap_or("").to_string(),
+        word_count: row.get("word_count"),
+        reading_minutes: row.get("reading_minutes"),
+        excerpt: row.get("excerpt"),
+    }
+}
+
+fn account_from_row(row: &Row) -> AccountModel {
+    let email: Option<&str> = row.get("email");
+    let description: Option<&str> = row.get("description");
+    let photo: Option<&str> = row.get("photo");
+    let site: Option<&str> = row.get("site");
+    let role: &str = row.get("role");
+
+    AccountModel {
+        pk: row.get("pk"),
+        uname: row.get("uname"),
+        nickname: row.get("nickname"),
+        email: email.unwrap_or("").to_string(),
+        description: description.unwrap_or("").to_string(),
+        photo: photo.unwrap_or("").to_string(),
+        site: site.unwrap_or("").to_string(),
+        role: AccountRole::parse(role).unwrap_or(AccountRole::Author),
+        create_time: row.get("create_time"),
+    }
+}
+
+fn revision_from_row(row: &Row) -> RevisionModel {
+    RevisionModel {
+        pk: row.get("pk"),
+        article: row.get("article"),
+        title: row.get("title"),
+        body: row.get("body"),
+        description: row.get("description"),
+        keywords: row.get("keywords"),
+        author: row.get("author"),
+        create_time: row.get("create_time"),
+    }
+}
+
+#[async_trait]
+impl ArticleRepository for PostgresRepository {
+    async fn list_published(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!(
+                    "{}\nwhere articles.status = 1 and articles.delete_time is null
+order by update_time desc offset $1 limit $2;",
+                    ARTICLE_SELECT
+                )
+                .as_str(),
+                &[&offset, &limit],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(article_from_row).collect())
+    }
+
+    async fn list_published_after(
+        &self,
+        after: Option<(chrono::NaiveDateTime, String)>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let (after_time, after_pk) = after.unzip();
+        let query_result = conn
+            .query(
+                format!(
+                    "{}\nwhere articles.status = 1 and articles.delete_time is null
+    and ($1::timestamp is null or (articles.create_time, articles.pk) > ($1, $2::varchar))
+order by articles.create_time, articles.pk offset $3 limit $4;",
+                    ARTICLE_SELECT
+                )
+                .as_str(),
+                &[&after_time, &after_pk, &offset, &limit],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(article_from_row).collect())
+    }
+
+    async fn list_all(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!(
+                    "{}\norder by update_time desc, articles.pk offset $1 limit $2;",
+                    ARTICLE_SELECT
+                )
+                .as_str(),
+                &[&offset, &limit],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(article_from_row).collect())
+    }
+
+    async fn list_published_by(
+        &self,
+        creator: Option<&str>,
+        keyword: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!(
+                    "{}\nwhere articles.status = 1 and articles.delete_time is null
+    and ($1::varchar is null or articles.creator = $1)
+    and ($2::varchar is null or $2 = any(regexp_split_to_array(articles.keywords, '\\s*[,，]\\s*')))
+order by update_time desc limit $3;",
+                    ARTICLE_SELECT
+                )
+                .as_str(),
+                &[&creator, &keyword, &limit],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(article_from_row).collect())
+    }
+
+    async fn count_published(&self) -> Result<i64, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let row = conn
+            .query_one(
+                "select count(*) from articles where status = 1 and delete_time is null;",
+                &[],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(row.get(0))
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!("{}\nwhere articles.pk = $1;", ARTICLE_SELECT).as_str(),
+                &[&pk],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(article_from_row))
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<ArticleModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!("{}\nwhere articles.slug = $1;", ARTICLE_SELECT).as_str(),
+                &[&slug],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(article_from_row))
+    }
+
+    async fn find_slug_history(&self, slug: &str) -> Result<Option<String>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                "select article from articles_slugs where slug = $1;",
+                &[&slug],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(|row| row.get("article")))
+    }
+
+    async fn insert_slug_history(
+        &self,
+        slug: &str,
+        article: &str,
+        create_time: chrono::NaiveDateTime,
+    ) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        conn.execute(
+            "insert into articles_slugs(slug, article, create_time) values($1, $2, $3)
+    on conflict (slug) do nothing;",
+            &[&slug, &article, &create_time],
+        )
+        .await
+        .map_err(AppError::Postgresql)?;
+        Ok(())
+    }
+
+    async fn list_creators(&self) -> Result<Vec<(String, chrono::NaiveDateTime)>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                "select articles.creator, max(articles.update_time) as update_time
+from articles
+where articles.status = 1 and articles.delete_time is null
+group by articles.creator
+order by update_time desc;",
+                &[],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result
+            .iter()
+            .map(|row| (row.get("creator"), row.get("update_time")))
+            .collect())
+    }
+
+    async fn insert(&self, model: &ArticleModel) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        conn.execute(
+            "insert into articles(pk, title, body, create_time, update_time, creator,
+                keywords, description, status, publish_at, word_count, reading_minutes, excerpt,
+                slug, template)
+    values($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, 1);",
+            &[
+                &model.pk,
+                &model.title,
+                &model.body,
+                &model.create_time,
+                &model.update_time,
+                &model.creator,
+                &model.keywords,
+                &model.description,
+                &(model.status as i32),
+                &model.publish_at,
+                &model.word_count,
+                &model.reading_minutes,
+                &model.excerpt,
+                &empty_as_null(&model.slug),
+            ],
+        )
+        .await
+        .map_err(article_write_error)?;
+        Ok(())
+    }
+
+    async fn update(&self, model: &ArticleModel) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        // 版本号不一致时不更新，由调用方区分文章不存在和版本冲突
+        let updated = conn
+            .execute(
+                "update articles set title = $2, body = $3, description = $4, keywords = $5,
+    status = $6, publish_at = $7, update_time = $8, delete_time = $9, word_count = $11,
+    reading_minutes = $12, excerpt = $13, slug = $14, version = version + 1
+where pk = $1 and version = $10;",
+                &[
+                    &model.pk,
+                    &model.title,
+                    &model.body,
+                    &model.description,
+                    &model.keywords,
+                    &(model.status as i32),
+                    &model.publish_at,
+                    &model.update_time,
+                    &model.delete_time,
+                    &model.version,
+                    &model.word_count,
+                    &model.reading_minutes,
+                    &model.excerpt,
+                    &empty_as_null(&model.slug),
+                ],
+            )
+            .await
+            .map_err(article_write_error)?;
+        if updated < 1 {
+            return match ArticleRepository::find(self, &model.pk).await? {
+                Some(current) => Err(AppError::Conflict {
+                    current_version: current.version,
+                }),
+                None => Err(AppError::NotFound),
+            };
+        }
+        Ok(())
+    }
+
+    async fn publish_due(&self, now: chrono::NaiveDateTime) -> Result<Vec<String>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                "update articles set status = 1, update_time = $1, version = version + 1
+where status = 3 and publish_at <= $1 and delete_time is null
+returning pk;",
+                &[&now],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(|row| row.get("pk")).collect())
+    }
+}
+
+#[async_trait]
+impl AccountRepository for PostgresRepository {
+    async fn insert(&self, model: &AccountModel) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        conn.execute(
+            "insert into accounts(pk, uname, nickname, email, description, photo, site, role,
+                create_time)
+    values($1, $2, $3, $4, $5, $6, $7, $8, $9);",
+            &[
+                &model.pk,
+                &model.uname,
+                &model.nickname,
+                &model.email,
+                &model.description,
+                &model.photo,
+                &model.site,
+                &model.role.as_str(),
+                &model.create_time,
+            ],
+        )
+        .await
+        .map_err(AppError::Postgresql)?;
+        Ok(())
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<AccountModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!("{}\nwhere accounts.pk = $1;", ACCOUNT_SELECT).as_str(),
+                &[&pk],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(account_from_row))
+    }
+
+    async fn find_by_uname(&self, uname: &str) -> Result<Option<AccountModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!("{}\nwhere accounts.uname = $1;", ACCOUNT_SELECT).as_str(),
+                &[&uname],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(account_from_row))
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for PostgresRepository {
+    async fn insert(&self, model: &RevisionModel) -> Result<(), AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        conn.execute(
+            "insert into articles_revisions(pk, article, title, body, description, keywords,
+                author, create_time)
+    values($1, $2, $3, $4, $5, $6, $7, $8);",
+            &[
+                &model.pk,
+                &model.article,
+                &model.title,
+                &model.body,
+                &model.description,
+                &model.keywords,
+                &model.author,
+                &model.create_time,
+            ],
+        )
+        .await
+        .map_err(AppError::Postgresql)?;
+        Ok(())
+    }
+
+    async fn list(&self, article: &str) -> Result<Vec<RevisionModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!(
+                    "{}\nwhere articles_revisions.article = $1\norder by create_time desc;",
+                    REVISION_SELECT
+                )
+                .as_str(),
+                &[&article],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.iter().map(revision_from_row).collect())
+    }
+
+    async fn find(&self, pk: &str) -> Result<Option<RevisionModel>, AppError> {
+        let conn = self.pool.get().await.map_err(OtherError::BB8Postgres)?;
+
+        let query_result = conn
+            .query(
+                format!("{}\nwhere articles_revisions.pk = $1;", REVISION_SELECT).as_str(),
+                &[&pk],
+            )
+            .await
+            .map_err(AppError::Postgresql)?;
+
+        Ok(query_result.first().map(revision_from_row))
+    }
+}
@@ -1,2 +1,4 @@
+pub(crate) mod account;
 pub(crate) mod article;
 pub(crate) mod index;
+pub(crate) mod scheduler;
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use nanoid::nanoid;
+
+use crate::handlers::State;
+use crate::models::account::{AccountModel, AccountRole};
+use crate::models::error::AppError;
+
+pub struct AccountService {
+    state: Arc<State>,
+}
+
+impl AccountService {
+    pub fn new(state: Arc<State>) -> AccountService {
+        AccountService { state }
+    }
+
+    pub async fn create(
+        &self,
+        uname: &str,
+        nickname: &str,
+        email: &str,
+        role: AccountRole,
+    ) -> Result<String, AppError> {
+        let model = AccountModel {
+            pk: nanoid!(12),
+            uname: uname.to_string(),
+            nickname: nickname.to_string(),
+            email: email.to_string(),
+            description: "".to_string(),
+            photo: "".to_string(),
+            site: "".to_string(),
+            role,
+            create_time: Utc::now().naive_utc(),
+        };
+        self.state.accounts.insert(&model).await?;
+
+        Ok(model.pk)
+    }
+
+    pub async fn find(&self, pk: &str) -> Result<AccountModel, AppError> {
+        self.state
+            .accounts
+            .find(pk)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn find_pk_by_uname(&self, uname: &str) -> Result<Option<String>, AppError> {
+        let model = self.state.accounts.find_by_uname(uname).await?;
+
+        Ok(model.map(|model| model.pk))
+    }
+}
@@ -1,8 +1,22 @@
 use crate::handlers::State;
-use crate::models::article::ArticleModel;
+use crate::models::account::{AccountModel, AccountRole};
+use crate::models::article::{ArticleModel, ArticleStatus};
 use crate::models::error::AppError;
+use crate::models::revision::RevisionModel;
+use crate::utils::slug;
+use chrono::Utc;
+use nanoid::nanoid;
 use std::sync::Arc;
 
+// 查找可用slug时最多尝试的序号
+const SLUG_MAX_SUFFIX: usize = 20;
+// slug因并发保存冲突时最多重试的次数
+const SLUG_SAVE_RETRIES: usize = 3;
+
+fn fallback_slug(base: &str, pk: &str) -> String {
+    format!("{}-{}", base, pk)
+}
+
 pub struct ArticleService {
     state: Arc<State>,
 }
@@ -24,62 +38,308 @@ impl ArticleService {
             limit as i64
         };
 
-        let conn = self
-            .state
-            .pool
-            .get()
+        self.state
+            .articles
+            .list_published(offset_value, limit_value)
             .await
-            .expect("graphql articles获取pool出错");
-
-        let query_result = conn
-            .query(
-                "select articles.pk, articles.title, articles.body, 
-articles.description, articles.update_time, articles.creator, articles.keywords,
-accounts.nickname, articles_views.views
-from articles
-    left join accounts on articles.creator = accounts.pk
-	left join articles_views on articles.pk = articles_views.pk
-where articles.status = 1
-order by update_time desc offset $1 limit $2;",
-                &[&offset_value, &limit_value],
-            )
+    }
+
+    // 不做分页参数的修正，由调用方校验
+    pub async fn list_all(&self, offset: i64, limit: i64) -> Result<Vec<ArticleModel>, AppError> {
+        self.state.articles.list_all(offset, limit).await
+    }
+
+    pub async fn list_feed(
+        &self,
+        creator: Option<&str>,
+        keyword: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleModel>, AppError> {
+        self.state
+            .articles
+            .list_published_by(creator, keyword, limit)
             .await
-            .expect("graphql articles执行查询出错");
+    }
 
-        let mut result: Vec<ArticleModel> = Vec::new();
+    pub async fn find(&self, pk: &str) -> Result<ArticleModel, AppError> {
+        self.state
+            .articles
+            .find(pk)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
 
-        for row in query_result {
-            let pk: &str = row.get("pk");
-            let title: &str = row.get("title");
+    pub async fn find_by_slug(&self, slug: &str) -> Result<Option<ArticleModel>, AppError> {
+        self.state.articles.find_by_slug(slug).await
+    }
+
+    // 标题修改前使用过的slug对应的文章
+    pub async fn find_by_old_slug(&self, slug: &str) -> Result<Option<ArticleModel>, AppError> {
+        match self.state.articles.find_slug_history(slug).await? {
+            Some(pk) => self.state.articles.find(&pk).await,
+            None => Ok(None),
+        }
+    }
+
+    // 只有文章的创建者可以修改文章
+    pub async fn find_owned(&self, pk: &str, user: &str) -> Result<ArticleModel, AppError> {
+        let model = self.find(pk).await?;
+        if model.creator != user {
+            return Err(AppError::Forbidden);
+        }
+        Ok(model)
+    }
 
-            let model = ArticleModel {
-                pk: pk.to_string(),
-                title: title.to_string(),
+    // 已删除的文章需要先恢复才能修改
+    pub async fn find_editable(&self, pk: &str, user: &str) -> Result<ArticleModel, AppError> {
+        let model = self.find_owned(pk, user).await?;
+        if model.delete_time.is_some() {
+            return Err(AppError::NotFound);
+        }
+        Ok(model)
+    }
+
+    pub async fn create(&self, mut model: ArticleModel) -> Result<ArticleModel, AppError> {
+        model.refresh_reading();
+        self.save(&mut model, true).await?;
+        self.record_revision(&model, &model.creator).await?;
+        Ok(model)
+    }
+
+    // 客户端提供了期望的版本时，与读取到的版本比较
+    pub fn check_version(
+        &self,
+        model: &ArticleModel,
+        expected_version: Option<i64>,
+    ) -> Result<(), AppError> {
+        match expected_version {
+            Some(version) if version != model.version => Err(AppError::Conflict {
+                current_version: model.version,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    // 只修改状态等元数据，不记录修订版本
+    pub async fn update(&self, mut model: ArticleModel) -> Result<ArticleModel, AppError> {
+        model.update_time = Utc::now().naive_utc();
+        model.refresh_reading();
+        let old_slug = model.slug.clone();
+        self.save(&mut model, false).await?;
+        self.state.render_cache.invalidate(&model.pk);
+        if !old_slug.is_empty() && old_slug != model.slug {
+            self.state
+                .articles
+                .insert_slug_history(&old_slug, &model.pk, model.update_time)
+                .await?;
+        }
+        model.version += 1;
+        Ok(model)
+    }
+
+    // 修改标题、正文等内容，同时记录一个修订版本
+    pub async fn update_content(
+        &self,
+        model: ArticleModel,
+        author: &str,
+    ) -> Result<ArticleModel, AppError> {
+        let model = self.update(model).await?;
+        self.record_revision(&model, author).await?;
+        Ok(model)
+    }
+
+    // 新生成的slug被同时保存的其他文章占用时，重新选择slug后再次保存
+    async fn save(&self, model: &mut ArticleModel, is_new: bool) -> Result<(), AppError> {
+        let old_slug = model.slug.clone();
+        let mut attempts = 0;
+        loop {
+            self.refresh_slug(model).await?;
+            let result = if is_new {
+                self.state.articles.insert(model).await
+            } else {
+                self.state.articles.update(model).await
             };
-            result.push(model);
+            match result {
+                Err(AppError::SlugTaken) if attempts < SLUG_SAVE_RETRIES => {
+                    attempts += 1;
+                    model.slug = old_slug.clone();
+                }
+                result => return result,
+            }
         }
+    }
 
-        Ok(result)
+    // 标题变化或还没有slug时重新生成
+    async fn refresh_slug(&self, model: &mut ArticleModel) -> Result<(), AppError> {
+        let mut base = slug::article_slug(&model.title);
+        if base.is_empty() {
+            base = model.pk.clone();
+        }
+        let unchanged =
+            slug::matches_base(&model.slug, &base) || model.slug == fallback_slug(&base, &model.pk);
+        if !model.slug.is_empty() && unchanged {
+            return Ok(());
+        }
+        model.slug = self.unique_slug(&model.pk, &base).await?;
+        Ok(())
     }
 
-    pub async fn query_count(&self) -> Result<i64, AppError> {
-        let conn = self
-            .state
-            .pool
-            .get()
-            .await
-            .expect("graphql articles获取pool出错");
+    // 其他文章正在使用或曾经使用过的slug依次加上-2、-3，尝试次数过多时改用pk作为后缀
+    async fn unique_slug(&self, pk: &str, base: &str) -> Result<String, AppError> {
+        let mut candidate = base.to_string();
+        for counter in 2..=SLUG_MAX_SUFFIX + 1 {
+            let current = self.state.articles.find_by_slug(&candidate).await?;
+            let history = self.state.articles.find_slug_history(&candidate).await?;
+            let taken = current.map_or(false, |article| article.pk != pk)
+                || history.map_or(false, |article| article != pk);
+            if !taken {
+                return Ok(candidate);
+            }
+            candidate = format!("{}-{}", base, counter);
+        }
+        Ok(fallback_slug(base, pk))
+    }
 
-        let query_result = conn
-            .query("select count(*) from articles where status = 1;", &[])
-            .await
-            .expect("graphql count执行查询出错");
+    async fn record_revision(&self, model: &ArticleModel, author: &str) -> Result<(), AppError> {
+        let revision = RevisionModel {
+            pk: nanoid!(12),
+            article: model.pk.clone(),
+            title: model.title.clone(),
+            body: model.body.clone(),
+            description: model.description.clone(),
+            keywords: model.keywords.clone(),
+            author: author.to_string(),
+            create_time: model.update_time,
+        };
+        self.state.revisions.insert(&revision).await
+    }
+
+    pub async fn list_revisions(&self, pk: &str) -> Result<Vec<RevisionModel>, AppError> {
+        self.state.revisions.list(pk).await
+    }
+
+    pub async fn find_revision(&self, pk: &str) -> Result<RevisionModel, AppError> {
+        self.state
+            .revisions
+            .find(pk)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    // 将旧的修订版本恢复为当前内容，恢复本身也会产生一个新的修订版本
+    pub async fn restore_revision(
+        &self,
+        mut model: ArticleModel,
+        revision_pk: &str,
+        author: &str,
+    ) -> Result<ArticleModel, AppError> {
+        let revision = self.find_revision(revision_pk).await?;
+        if revision.article != model.pk {
+            return Err(AppError::InvalidParameter);
+        }
+        model.title = revision.title;
+        model.body = revision.body;
+        model.description = revision.description;
+        model.keywords = revision.keywords;
+        self.update_content(model, author).await
+    }
+
+    // author只能在草稿和审核之间切换自己的文章，其余变更需要editor
+    pub fn check_transition(
+        &self,
+        model: &ArticleModel,
+        to: ArticleStatus,
+        actor: &AccountModel,
+    ) -> Result<(), AppError> {
+        if !model.status.can_transition_to(to) {
+            return Err(AppError::InvalidTransition {
+                from: model.status,
+                to,
+            });
+        }
+        let allowed = match actor.role {
+            AccountRole::Editor => true,
+            AccountRole::Author => {
+                model.creator == actor.pk
+                    && matches!(
+                        (model.status, to),
+                        (ArticleStatus::Draft, ArticleStatus::Review)
+                            | (ArticleStatus::Review, ArticleStatus::Draft)
+                    )
+            }
+        };
+        if !allowed {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
 
-        for row in query_result {
-            let count: i64 = row.get(0);
-            return Ok(count as i64);
+    pub async fn transition(
+        &self,
+        mut model: ArticleModel,
+        to: ArticleStatus,
+        publish_at: Option<chrono::NaiveDateTime>,
+        actor: &AccountModel,
+    ) -> Result<ArticleModel, AppError> {
+        // 与find_editable一致，已删除的文章需要先恢复
+        if model.delete_time.is_some() {
+            return Err(AppError::NotFound);
+        }
+        self.check_transition(&model, to, actor)?;
+        self.apply_status(&mut model, to, publish_at)?;
+        self.update(model).await
+    }
+
+    // 命令行以管理员身份执行，只校验状态之间能否变更
+    pub async fn transition_as_admin(
+        &self,
+        pk: &str,
+        to: ArticleStatus,
+        publish_at: Option<chrono::NaiveDateTime>,
+    ) -> Result<ArticleModel, AppError> {
+        let mut model = self.find(pk).await?;
+        if model.delete_time.is_some() {
+            return Err(AppError::NotFound);
+        }
+        if !model.status.can_transition_to(to) {
+            return Err(AppError::InvalidTransition {
+                from: model.status,
+                to,
+            });
         }
+        self.apply_status(&mut model, to, publish_at)?;
+        self.update(model).await
+    }
+
+    pub fn apply_status(
+        &self,
+        model: &mut ArticleModel,
+        to: ArticleStatus,
+        publish_at: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), AppError> {
+        model.publish_at = match to {
+            ArticleStatus::Scheduled => {
+                let publish_at = publish_at.ok_or(AppError::InvalidParameter)?;
+                if publish_at <= Utc::now().naive_utc() {
+                    return Err(AppError::InvalidParameter);
+                }
+                Some(publish_at)
+            }
+            _ => None,
+        };
+        model.status = to;
+        Ok(())
+    }
 
-        Err(AppError::EmptyData)
+    // 由后台任务定期调用
+    pub async fn publish_due(&self) -> Result<Vec<String>, AppError> {
+        self.state
+            .articles
+            .publish_due(Utc::now().naive_utc())
+            .await
+    }
+
+    pub async fn query_count(&self) -> Result<i64, AppError> {
+        self.state.articles.count_published().await
     }
 }
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::handlers::State;
+use crate::service::article::ArticleService;
+
+// 定时发布的检查间隔，文章的实际发布时间最多比publish_at晚这么久
+const PUBLISH_INTERVAL_SECONDS: u64 = 30;
+
+/// 后台定期将到期的定时文章改为已发布
+pub fn spawn_publisher(state: Arc<State>) {
+    tokio::spawn(async move {
+        let article_service = ArticleService::new(state);
+        let mut interval = tokio::time::interval(Duration::from_secs(PUBLISH_INTERVAL_SECONDS));
+        loop {
+            interval.tick().await;
+            match article_service.publish_due().await {
+                Ok(published) => {
+                    if !published.is_empty() {
+                        tracing::info!("已定时发布文章: {}", published.join(", "));
+                    }
+                }
+                Err(err) => tracing::warn!("定时发布文章出错: {}", err),
+            }
+        }
+    });
+}
@@ -1,17 +1,27 @@
 pub(crate) mod article;
-pub(crate) mod env;
+pub(crate) mod diff;
+pub(crate) mod export;
+pub(crate) mod highlight;
+pub(crate) mod inline;
+pub(crate) mod markdown;
+pub(crate) mod metadata;
+pub(crate) mod reading;
+pub(crate) mod render;
+pub(crate) mod render_cache;
+pub(crate) mod sanitize;
+pub(crate) mod slug;
 
-use crate::config;
+use crate::config::ProximaConfig;
 
-pub fn get_photo_or_default(photo_path: &str) -> String {
+pub fn get_photo_or_default(config: &ProximaConfig, photo_path: &str) -> String {
     if !photo_path.is_empty() {
         if photo_path.starts_with("http://") || photo_path.starts_with("https://") {
             return photo_path.to_string();
         }
         let mut file_url = "".to_string();
-        file_url.push_str(config::FILE_URL);
+        file_url.push_str(config.file_url.as_str());
         file_url.push_str(photo_path);
         return file_url;
     }
-    config::DEFAULT_FILE_URL.to_string()
+    config.default_file_url.to_string()
 }
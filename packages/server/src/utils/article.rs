@@ -4,68 +4,324 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::handlers::State;
+use crate::models::article::DocumentFormat;
+use crate::models::error::{AppError, OtherError};
+use crate::utils::render::{RenderContext, RendererRegistry};
+use crate::utils::slug::SlugSet;
+use crate::utils::{highlight, markdown};
 use crate::{layers, utils};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TocItem {
     pub title: String,
     pub header: i32,
+    // 对应标题元素的id
+    pub id: String,
+    pub children: Vec<TocItem>,
 }
 
-pub fn build_body(toc_box: &mut Vec<TocItem>, nodes: &serde_json::Value) -> Result<String, String> {
-    let children = nodes["children"]
+/// 渲染过程中按顺序收集标题，并保证标题id在文章内唯一
+#[derive(Debug, Default)]
+pub struct Toc {
+    items: Vec<TocItem>,
+    slugs: SlugSet,
+}
+
+impl Toc {
+    pub fn new() -> Toc {
+        Toc::default()
+    }
+
+    // 返回标题元素应使用的id
+    pub fn add(&mut self, title: &str, header: i32) -> String {
+        let id = self.slugs.unique(title);
+        self.items.push(TocItem {
+            title: title.to_string(),
+            header,
+            id: id.clone(),
+            children: Vec::new(),
+        });
+        id
+    }
+
+    // 按标题级别组成树，每个标题挂在它之前最近的更高级别标题下
+    pub fn into_tree(self) -> Vec<TocItem> {
+        let mut roots: Vec<TocItem> = Vec::new();
+        let mut stack: Vec<TocItem> = Vec::new();
+        for item in self.items {
+            while stack
+                .last()
+                .map_or(false, |last| last.header >= item.header)
+            {
+                let done = stack.pop().unwrap();
+                attach(&mut roots, &mut stack, done);
+            }
+            stack.push(item);
+        }
+        while let Some(done) = stack.pop() {
+            attach(&mut roots, &mut stack, done);
+        }
+        roots
+    }
+}
+
+fn attach(roots: &mut Vec<TocItem>, stack: &mut [TocItem], item: TocItem) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(item),
+        None => roots.push(item),
+    }
+}
+
+// 解析客户端提交的正文，Markdown转换为文档模型；
+// 文档可以是完整的文档，也可以只是children数组
+pub fn parse_document(body: &str, format: DocumentFormat) -> Result<serde_json::Value, AppError> {
+    if format == DocumentFormat::Markdown {
+        return Ok(markdown::markdown_to_document(body));
+    }
+    let document: serde_json::Value = serde_json::from_str(body).map_err(OtherError::Unknown)?;
+    if document.is_array() {
+        return Ok(json!({ "children": document }));
+    }
+    if !document["children"].is_array() {
+        return Err(AppError::InvalidData);
+    }
+    Ok(document)
+}
+
+pub fn register_defaults(registry: &mut RendererRegistry) {
+    registry
+        .register("paragraph", build_paragraph)
+        .register("header", build_header)
+        .register("code-block", build_code_block)
+        .register("bulleted-list", build_bulleted_list)
+        .register("unordered-list", build_bulleted_list)
+        .register("numbered-list", build_numbered_list)
+        .register("ordered-list", build_numbered_list)
+        .register("block-quote", build_quote)
+        .register("image", build_image)
+        .register("link", build_link)
+        .register("hr", build_divider)
+        .register("divider", build_divider)
+        .register("table", build_table);
+}
+
+// 无法识别的节点只保留其中的文本
+pub fn build_fallback(node: &serde_json::Value) -> String {
+    let text = plain_text(node);
+    if text.is_empty() {
+        return "".to_string();
+    }
+    format!(
+        "<div class='fx-unknown'>{}</div>",
+        html_escape::encode_text(&text)
+    )
+}
+
+pub fn plain_text(node: &serde_json::Value) -> String {
+    if let Some(text) = node["text"].as_str() {
+        return text.to_string();
+    }
+    node["children"]
         .as_array()
-        .ok_or_else(|| "children未定义")?;
+        .map_or("".to_string(), |children| {
+            children
+                .iter()
+                .map(plain_text)
+                .collect::<Vec<String>>()
+                .join("")
+        })
+}
+
+fn build_quote(ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+    Ok(format!(
+        "<blockquote class='fx-quote'>{}</blockquote>",
+        ctx.render_children(node)?
+    ))
+}
+
+fn build_divider(_ctx: &mut RenderContext, _node: &serde_json::Value) -> Result<String, String> {
+    Ok("<hr class='fx-divider'/>".to_string())
+}
+
+fn build_bulleted_list(
+    ctx: &mut RenderContext,
+    node: &serde_json::Value,
+) -> Result<String, String> {
+    build_list(ctx, node, "ul")
+}
+
+fn build_numbered_list(
+    ctx: &mut RenderContext,
+    node: &serde_json::Value,
+) -> Result<String, String> {
+    build_list(ctx, node, "ol")
+}
 
-    let mut body_html_builder = string_builder::Builder::default();
+fn build_list(
+    ctx: &mut RenderContext,
+    node: &serde_json::Value,
+    tag: &str,
+) -> Result<String, String> {
+    let children = node["children"]
+        .as_array()
+        .ok_or_else(|| "list children未定义")?;
 
+    let mut html = match node["start"].as_u64() {
+        Some(start) if tag == "ol" && start != 1 => {
+            format!("<{} class='fx-list' start='{}'>", tag, start)
+        }
+        _ => format!("<{} class='fx-list'>", tag),
+    };
     for child in children {
-        let content = build_node(toc_box, &child).or_else(|err| Err(err.to_string()))?;
-        body_html_builder.append(content);
+        match child["name"].as_str() {
+            // 嵌套列表可以直接作为列表的子节点
+            Some("bulleted-list" | "unordered-list" | "numbered-list" | "ordered-list") => {
+                html.push_str(&ctx.render_block(child))
+            }
+            _ => html.push_str(&format!("<li>{}</li>", ctx.render_children(child)?)),
+        }
     }
-    match body_html_builder.string() {
-        Ok(v) => Ok(v),
-        Err(err) => Err(err.to_string()),
+    html.push_str(&format!("</{}>", tag));
+    Ok(html)
+}
+
+// 正文中的图片，站内相对地址补全为绝对地址
+pub fn image_urls(site_url: &str, document: &serde_json::Value) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut stack: Vec<&serde_json::Value> = vec![document];
+    while let Some(node) = stack.pop() {
+        if node["name"].as_str() == Some("image") {
+            let url = node["url"].as_str().or_else(|| node["src"].as_str());
+            match url {
+                Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                    urls.push(url.to_string())
+                }
+                Some(url) if url.starts_with('/') && !url.starts_with("//") => {
+                    urls.push(format!("{}{}", site_url, url))
+                }
+                _ => {}
+            }
+        }
+        if let Some(children) = node["children"].as_array() {
+            stack.extend(children.iter().rev());
+        }
+    }
+    urls
+}
+
+// 只允许http(s)、mailto、站内相对地址及锚点，避免javascript:等协议
+pub fn safe_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let lower = url.to_lowercase();
+    let allowed = ["http://", "https://", "mailto:", "/", "#"]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+        || !lower.contains(':');
+    if url.is_empty() || !allowed {
+        return None;
+    }
+    Some(html_escape::encode_single_quoted_attribute(url).to_string())
+}
+
+fn build_link(ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+    let url = node["url"]
+        .as_str()
+        .or_else(|| node["href"].as_str())
+        .ok_or_else(|| "未找到url属性")?;
+    let mut content = String::new();
+    for child in node["children"].as_array().unwrap_or(&Vec::new()) {
+        content.push_str(&ctx.render_node(child)?);
+    }
+    if content.is_empty() {
+        content = html_escape::encode_text(url).to_string();
+    }
+    match safe_url(url) {
+        Some(href) => Ok(format!(
+            "<a class='fx-link' href='{}' rel='noopener noreferrer'>{}</a>",
+            href, content
+        )),
+        None => Ok(content),
+    }
+}
+
+fn build_image(_ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+    let url = node["url"]
+        .as_str()
+        .or_else(|| node["src"].as_str())
+        .ok_or_else(|| "未找到url属性")?;
+    let src = safe_url(url).ok_or_else(|| format!("不允许的图片地址: {}", url))?;
+    let alt = node["alt"].as_str().unwrap_or("");
+    let caption = node["caption"].as_str().unwrap_or("");
+
+    let mut html = format!(
+        "<figure class='fx-image'><img src='{}' alt='{}' loading='lazy'/>",
+        src,
+        html_escape::encode_single_quoted_attribute(alt)
+    );
+    if !caption.is_empty() {
+        html.push_str(&format!(
+            "<figcaption>{}</figcaption>",
+            html_escape::encode_text(caption)
+        ));
     }
+    html.push_str("</figure>");
+    Ok(html)
 }
 
-fn build_node(toc_box: &mut Vec<TocItem>, node: &serde_json::Value) -> Result<String, String> {
-    let name = node["name"].as_str().ok_or_else(|| "未找到name属性")?;
-    match name {
-        "paragraph" => Ok(build_paragraph(node)?),
-        "header" => Ok(build_header(toc_box, node)?),
-        "code-block" => Ok("code-block".to_string()),
-        _ => Err("undefined".to_string()),
+// table > table-row > table-cell，单元格的header属性或table-header-cell表示表头
+fn build_table(ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+    let rows = node["children"]
+        .as_array()
+        .ok_or_else(|| "table children未定义")?;
+
+    let mut html = "<div class='fx-table-wrapper'><table class='fx-table'>".to_string();
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row["children"].as_array().unwrap_or(&Vec::new()) {
+            let is_header = cell["header"].as_bool().unwrap_or(false)
+                || cell["name"].as_str() == Some("table-header-cell");
+            let tag = if is_header { "th" } else { "td" };
+            let mut content = String::new();
+            for child in cell["children"].as_array().unwrap_or(&Vec::new()) {
+                content.push_str(&ctx.render_node(child)?);
+            }
+            html.push_str(&format!("<{}>{}</{}>", tag, content, tag));
+        }
+        html.push_str("</tr>");
     }
+    html.push_str("</table></div>");
+    Ok(html)
 }
 
-fn build_header(toc_list: &mut Vec<TocItem>, node: &serde_json::Value) -> Result<String, String> {
+fn build_header(ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
     let header = node["header"].as_i64().ok_or_else(|| "未找到header属性")?;
 
+    let header = header.clamp(1, 6);
+
     let children = node["children"]
         .as_array()
         .ok_or_else(|| "header children未定义")?;
-    let mut header_text: String = "".to_string();
-
+    let mut header_html_content = String::new();
     for child in children {
-        let content = build_header_text(&child).or_else(|err| Err(err.to_string()))?;
-        header_text.push_str(content.as_str());
-        toc_list.push(TocItem {
-            title: header_text.to_string(),
-            header: header as i32,
-        })
+        header_html_content.push_str(&ctx.render_node(child)?);
     }
+
+    // 多个带样式的文本节点合并为一个目录项
+    let id = ctx.toc.add(plain_text(node).trim(), header as i32);
     let header_html = format!(
         "<h{} id='{}'>{}</h{}>",
-        header, header_text, header_text, header
+        header,
+        html_escape::encode_single_quoted_attribute(&id),
+        header_html_content,
+        header
     );
 
     Ok(header_html)
 }
 
-fn build_paragraph(node: &serde_json::Value) -> Result<String, String> {
+fn build_paragraph(ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
     let children = node["children"]
         .as_array()
         .ok_or_else(|| "paragraph children未定义")?;
@@ -73,7 +329,7 @@ fn build_paragraph(node: &serde_json::Value) -> Result<String, String> {
     let mut children_html_builder = string_builder::Builder::default();
     children_html_builder.append("<p class='fx-paragraph'>");
     for child in children {
-        let content = build_text(&child).or_else(|err| Err(err.to_string()))?;
+        let content = ctx.render_node(&child)?;
         children_html_builder.append(content.replace("\n", "<br/>"));
     }
     children_html_builder.append("</p>");
@@ -83,32 +339,178 @@ fn build_paragraph(node: &serde_json::Value) -> Result<String, String> {
     }
 }
 
-fn build_text(node: &serde_json::Value) -> Result<String, String> {
-    let text = node["text"].as_str().ok_or_else(|| "未找到text属性")?;
+// 代码块的children为code-line节点，每个code-line的children为文本节点；
+// 也兼容直接以文本节点作为children
+fn code_block_source(node: &serde_json::Value) -> Result<String, String> {
+    let children = node["children"]
+        .as_array()
+        .ok_or_else(|| "code-block children未定义")?;
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut text = String::new();
+    for child in children {
+        match child["children"].as_array() {
+            Some(leaves) => {
+                let line: String = leaves
+                    .iter()
+                    .filter_map(|leaf| leaf["text"].as_str())
+                    .collect();
+                lines.push(line);
+            }
+            None => text.push_str(child["text"].as_str().ok_or_else(|| "未找到text属性")?),
+        }
+    }
+    if lines.is_empty() {
+        return Ok(text.trim_end_matches('\n').to_string());
+    }
+    Ok(lines.join("\n"))
+}
 
-    let text_html = html_escape::encode_text(text);
-    let mut text_decoration: String = "".to_string();
-    let mut class_name: String = "".to_string();
+// highlightLines可以是行号数组，也可以是"1,3-5"形式的字符串，超出line_count的行号被忽略
+fn parse_highlight_lines(value: &serde_json::Value, line_count: usize) -> HashSet<usize> {
+    if let Some(numbers) = value.as_array() {
+        return numbers
+            .iter()
+            .filter_map(|number| number.as_u64())
+            .map(|number| number as usize)
+            .filter(|number| (1..=line_count).contains(number))
+            .collect();
+    }
+    let mut result: HashSet<usize> = HashSet::new();
+    for part in value.as_str().unwrap_or("").split(',') {
+        let mut range = part.trim().splitn(2, '-');
+        let start = range.next().and_then(|v| v.trim().parse::<usize>().ok());
+        let end = range.next().and_then(|v| v.trim().parse::<usize>().ok());
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                result.extend(start.max(1)..=end.min(line_count))
+            }
+            (Some(start), None) if (1..=line_count).contains(&start) => {
+                result.insert(start);
+            }
+            _ => {}
+        }
+    }
+    result
+}
 
-    text_decoration.push_str(node["strike"].as_str().map_or("", |_| "line-through"));
-    class_name.push_str(node["bold"].as_str().map_or("", |_| "fx-bold"));
-    class_name.push_str(node["italic"].as_str().map_or("", |_| "fx-italic"));
-    text_decoration.push_str(node["underline"].as_str().map_or("", |_| "underline"));
-    class_name.push_str(node["code"].as_str().map_or("", |_| "fx-code"));
+fn build_code_block(_ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+    let source = code_block_source(node)?;
+    let language_name = node["language"].as_str().unwrap_or("");
+    let language = highlight::find_language(language_name);
+    let language_class = language.map_or("plaintext", |language| language.name);
+    let line_numbers = node["lineNumbers"].as_bool().unwrap_or(false);
 
-    let mut property: String = "".to_string();
-    if !class_name.is_empty() {
-        property = format!(" class='{}'", class_name);
+    let mut code_html_builder = string_builder::Builder::default();
+    code_html_builder.append(format!(
+        "<figure class='fx-code-block' data-language='{}'>",
+        language_class
+    ));
+    if !language_name.is_empty() {
+        code_html_builder.append(format!(
+            "<figcaption class='fx-code-language'>{}</figcaption>",
+            html_escape::encode_text(language_name)
+        ));
     }
-    if !text_decoration.is_empty() {
-        property.push_str(format!(" style='text-decoration:{}'", text_decoration).as_str());
+    code_html_builder.append(format!(
+        "<pre class='fx-code language-{}'><code>",
+        language_class
+    ));
+    let lines = highlight::highlight_lines(language, &source);
+    let highlight_lines = parse_highlight_lines(&node["highlightLines"], lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let number = index + 1;
+        let mut class_name = "fx-code-line".to_string();
+        if highlight_lines.contains(&number) {
+            class_name.push_str(" fx-code-line-highlight");
+        }
+        code_html_builder.append(format!(
+            "<span class='{}' data-line='{}'>",
+            class_name, number
+        ));
+        if line_numbers {
+            // 行号不参与选择和复制
+            code_html_builder.append(format!(
+                "<span class='fx-line-number' aria-hidden='true'>{}</span>",
+                number
+            ));
+        }
+        code_html_builder.append(format!("{}\n</span>", line));
     }
+    code_html_builder.append("</code></pre>");
+    // 不带高亮和行号的原始代码，供复制按钮使用
+    code_html_builder.append(format!(
+        "<pre class='fx-code-raw' hidden><code>{}</code></pre>",
+        html_escape::encode_text(&source)
+    ));
+    code_html_builder.append("</figure>");
 
-    Ok(format!("<span {}>{}</span>", property, text_html))
+    match code_html_builder.string() {
+        Ok(v) => Ok(v),
+        Err(err) => Err(err.to_string()),
+    }
 }
 
-fn build_header_text(node: &serde_json::Value) -> Result<String, String> {
-    let text = node["text"].as_str().ok_or_else(|| "未找到text属性")?;
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde_json::json;
+
+    use super::{parse_highlight_lines, Toc, TocItem};
+
+    fn sorted(lines: HashSet<usize>) -> Vec<usize> {
+        let mut lines: Vec<usize> = lines.into_iter().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn highlight_lines_are_clamped_to_the_block() {
+        assert_eq!(
+            sorted(parse_highlight_lines(&json!("0-2, 4, 9, 3-100"), 5)),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            sorted(parse_highlight_lines(&json!([0, 2, 6, -1]), 5)),
+            vec![2]
+        );
+        assert!(parse_highlight_lines(&json!("5-3,x"), 5).is_empty());
+        assert!(parse_highlight_lines(&json!(true), 5).is_empty());
+    }
 
-    Ok(html_escape::encode_text(text).to_string())
+    fn titles(items: &[TocItem]) -> Vec<(String, Vec<String>)> {
+        items
+            .iter()
+            .map(|item| {
+                (
+                    item.title.clone(),
+                    item.children
+                        .iter()
+                        .map(|child| child.title.clone())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn toc_tree_handles_level_jumps() {
+        let mut toc = Toc::new();
+        toc.add("a", 2);
+        toc.add("b", 4);
+        toc.add("c", 3);
+        toc.add("d", 2);
+        toc.add("a", 3);
+        let tree = toc.into_tree();
+        assert_eq!(
+            titles(&tree),
+            vec![
+                ("a".to_string(), vec!["b".to_string(), "c".to_string()]),
+                ("d".to_string(), vec!["a".to_string()]),
+            ]
+        );
+        assert_eq!(tree[1].children[0].id, "a-2");
+        assert_eq!(tree[1].children[0].header, 3);
+    }
 }
@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NodeDiff {
+    pub op: DiffOp,
+    pub node: serde_json::Value,
+    // 节点在旧文档和新文档children中的位置
+    pub old_index: Option<usize>,
+    pub new_index: Option<usize>,
+}
+
+/// 以文档的顶层节点为单位比较两个文档，基于最长公共子序列
+pub fn diff_documents(old: &serde_json::Value, new: &serde_json::Value) -> Vec<NodeDiff> {
+    let empty: Vec<serde_json::Value> = Vec::new();
+    let old_nodes = old["children"].as_array().unwrap_or(&empty);
+    let new_nodes = new["children"].as_array().unwrap_or(&empty);
+    let (n, m) = (old_nodes.len(), new_nodes.len());
+
+    // lcs[i][j] 为 old_nodes[i..] 与 new_nodes[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_nodes[i] == new_nodes[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result: Vec<NodeDiff> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_nodes[i] == new_nodes[j] {
+            result.push(NodeDiff {
+                op: DiffOp::Equal,
+                node: new_nodes[j].clone(),
+                old_index: Some(i),
+                new_index: Some(j),
+            });
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            result.push(NodeDiff {
+                op: DiffOp::Insert,
+                node: new_nodes[j].clone(),
+                old_index: None,
+                new_index: Some(j),
+            });
+            j += 1;
+        } else {
+            result.push(NodeDiff {
+                op: DiffOp::Delete,
+                node: old_nodes[i].clone(),
+                old_index: Some(i),
+                new_index: None,
+            });
+            i += 1;
+        }
+    }
+    result
+}
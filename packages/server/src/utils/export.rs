@@ -0,0 +1,444 @@
+use serde_json::Value;
+
+use crate::utils::article::plain_text;
+use crate::utils::inline::Marks;
+
+// 将文档模型导出为CommonMark（表格使用GFM语法），与utils::markdown互为逆过程
+pub fn document_to_markdown(document: &Value) -> String {
+    blocks_to_markdown(document).trim_end().to_string() + "\n"
+}
+
+// 导出为纯文本，用于全文检索等不需要格式的场景
+pub fn document_to_text(document: &Value) -> String {
+    blocks_to_text(document).trim_end().to_string() + "\n"
+}
+
+fn children(node: &Value) -> &[Value] {
+    node["children"]
+        .as_array()
+        .map(|children| children.as_slice())
+        .unwrap_or(&[])
+}
+
+fn is_inline(node: &Value) -> bool {
+    node["text"].is_string() || node["name"].as_str() == Some("link")
+}
+
+// 行首的列表、setext标题和代码围栏符号也需要转义，
+// 文本节点不知道自己是否在行首，开头按行首处理，多余的转义不影响结果
+fn escape_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut line_start = true;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if line_start {
+            if matches!(c, '-' | '+' | '=' | '~') {
+                result.push('\\');
+            } else if c.is_ascii_digit() {
+                let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+                if matches!(chars.get(i + digits), Some('.' | ')')) {
+                    result.extend(&chars[i..i + digits]);
+                    result.push('\\');
+                    i += digits;
+                    line_start = false;
+                    continue;
+                }
+            }
+        }
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            result.push('\\');
+        }
+        result.push(c);
+        line_start = c == '\n' || (line_start && c == ' ');
+        i += 1;
+    }
+    result
+}
+
+// 包含空白或括号的地址放在<>中，反斜杠在两种写法中都会被当作转义
+fn link_destination(url: &str) -> String {
+    let url = url.replace('\\', "\\\\");
+    if url
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '(' | ')' | '<' | '>'))
+    {
+        format!(
+            "<{}>",
+            url.replace('<', "\\<")
+                .replace('>', "\\>")
+                .replace('\n', "%0A")
+        )
+    } else {
+        url
+    }
+}
+
+// 为多行内容的每一行加上前缀，首行可以使用不同的前缀（如列表符号）
+fn prefix_lines(content: &str, first: &str, rest: &str) -> String {
+    content
+        .trim_end()
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let prefix = if index == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// 围栏比文本中最长的连续反引号多一个，文本以反引号开头或结尾时用空格隔开
+fn code_span(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let fence = "`".repeat(longest + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", fence, text, fence)
+    } else {
+        format!("{}{}{}", fence, text, fence)
+    }
+}
+
+fn inline_to_markdown(node: &Value) -> String {
+    if let Some(text) = node["text"].as_str() {
+        let marks = Marks::from_node(node);
+        let mut result = if marks.code {
+            code_span(text)
+        } else {
+            escape_markdown(text).replace('\n', "  \n")
+        };
+        if marks.bold {
+            result = format!("**{}**", result);
+        }
+        if marks.italic {
+            result = format!("_{}_", result);
+        }
+        if marks.strike {
+            result = format!("~~{}~~", result);
+        }
+        if let Some(link) = node["link"].as_str() {
+            result = format!("[{}]({})", result, link_destination(link));
+        }
+        return result;
+    }
+    match node["name"].as_str() {
+        Some("link") => format!(
+            "[{}]({})",
+            children(node)
+                .iter()
+                .map(inline_to_markdown)
+                .collect::<String>(),
+            link_destination(
+                node["url"]
+                    .as_str()
+                    .or_else(|| node["href"].as_str())
+                    .unwrap_or("")
+            )
+        ),
+        _ => escape_markdown(&plain_text(node)),
+    }
+}
+
+fn children_to_markdown(node: &Value) -> String {
+    if children(node).iter().all(is_inline) {
+        return children(node).iter().map(inline_to_markdown).collect();
+    }
+    blocks_to_markdown(node)
+}
+
+fn blocks_to_markdown(node: &Value) -> String {
+    let mut result = String::new();
+    for child in children(node) {
+        let block = block_to_markdown(child);
+        if !block.is_empty() {
+            result.push_str(block.trim_end());
+            result.push_str("\n\n");
+        }
+    }
+    result
+}
+
+fn list_to_markdown(node: &Value, ordered: bool) -> String {
+    let start = node["start"].as_u64().unwrap_or(1);
+    let mut items: Vec<String> = Vec::new();
+    for (index, item) in children(node).iter().enumerate() {
+        let marker = if ordered {
+            format!("{}. ", start + index as u64)
+        } else {
+            "- ".to_string()
+        };
+        let indent = " ".repeat(marker.len());
+        let content = match item["name"].as_str() {
+            Some("bulleted-list" | "unordered-list" | "numbered-list" | "ordered-list") => {
+                // 直接嵌套的列表缩进到上一项之下
+                items.push(prefix_lines(&block_to_markdown(item), &indent, &indent));
+                continue;
+            }
+            _ => children_to_markdown(item),
+        };
+        items.push(prefix_lines(&content, &marker, &indent));
+    }
+    items.join("\n")
+}
+
+fn table_to_markdown(node: &Value) -> String {
+    let rows: Vec<Vec<String>> = children(node)
+        .iter()
+        .map(|row| {
+            children(row)
+                .iter()
+                .map(|cell| children_to_markdown(cell).replace('\n', " "))
+                .collect()
+        })
+        .collect();
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return "".to_string();
+    }
+
+    let format_row = |row: &Vec<String>| {
+        let mut cells = row.clone();
+        cells.resize(columns, "".to_string());
+        format!("| {} |", cells.join(" | "))
+    };
+    // 第一行作为表头
+    let mut lines = vec![
+        format_row(&rows[0]),
+        format!("|{}", " --- |".repeat(columns)),
+    ];
+    lines.extend(rows.iter().skip(1).map(format_row));
+    lines.join("\n")
+}
+
+fn block_to_markdown(node: &Value) -> String {
+    if is_inline(node) {
+        return inline_to_markdown(node);
+    }
+    match node["name"].as_str().unwrap_or("") {
+        "paragraph" => children_to_markdown(node),
+        "header" => {
+            let level = node["header"].as_u64().unwrap_or(1).clamp(1, 6) as usize;
+            // ATX标题只能占一行，换行改为空格
+            let content = children_to_markdown(node)
+                .lines()
+                .map(|line| line.trim_end())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            format!("{} {}", "#".repeat(level), content)
+        }
+        "code-block" => {
+            let code = code_text(node);
+            // 代码中包含```时使用更长的围栏
+            let mut fence = "```".to_string();
+            while code.contains(&fence) {
+                fence.push('`');
+            }
+            format!(
+                "{}{}\n{}\n{}",
+                fence,
+                node["language"].as_str().unwrap_or(""),
+                code,
+                fence
+            )
+        }
+        "bulleted-list" | "unordered-list" => list_to_markdown(node, false),
+        "numbered-list" | "ordered-list" => list_to_markdown(node, true),
+        "block-quote" => prefix_lines(&children_to_markdown(node), "> ", "> "),
+        "image" => {
+            let url = link_destination(
+                node["url"]
+                    .as_str()
+                    .or_else(|| node["src"].as_str())
+                    .unwrap_or(""),
+            );
+            match node["caption"]
+                .as_str()
+                .filter(|caption| !caption.is_empty())
+            {
+                Some(caption) => format!(
+                    "![{}]({} \"{}\")",
+                    escape_markdown(node["alt"].as_str().unwrap_or("")),
+                    url,
+                    caption.replace('\\', "\\\\").replace('"', "\\\"")
+                ),
+                None => format!(
+                    "![{}]({})",
+                    escape_markdown(node["alt"].as_str().unwrap_or("")),
+                    url
+                ),
+            }
+        }
+        "hr" | "divider" => "---".to_string(),
+        "table" => table_to_markdown(node),
+        _ => escape_markdown(&plain_text(node)),
+    }
+}
+
+// 代码块中每个code-line为一行
+fn code_text(node: &Value) -> String {
+    let lines = children(node);
+    if lines.iter().all(|line| line["text"].is_string()) {
+        return plain_text(node).trim_end_matches('\n').to_string();
+    }
+    lines
+        .iter()
+        .map(plain_text)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn blocks_to_text(node: &Value) -> String {
+    let mut result = String::new();
+    for child in children(node) {
+        let block = block_to_text(child);
+        if !block.trim().is_empty() {
+            result.push_str(block.trim_end());
+            result.push_str("\n\n");
+        }
+    }
+    result
+}
+
+fn children_to_text(node: &Value) -> String {
+    if children(node).iter().all(is_inline) {
+        return plain_text(node);
+    }
+    blocks_to_text(node)
+}
+
+fn block_to_text(node: &Value) -> String {
+    if is_inline(node) {
+        return plain_text(node);
+    }
+    match node["name"].as_str().unwrap_or("") {
+        "code-block" => code_text(node),
+        "bulleted-list" | "unordered-list" | "numbered-list" | "ordered-list" => children(node)
+            .iter()
+            .map(|item| prefix_lines(&children_to_text(item), "- ", "  "))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        "image" => node["alt"]
+            .as_str()
+            .or_else(|| node["caption"].as_str())
+            .unwrap_or("")
+            .to_string(),
+        "hr" | "divider" => "".to_string(),
+        "table" => children(node)
+            .iter()
+            .map(|row| {
+                children(row)
+                    .iter()
+                    .map(children_to_text)
+                    .map(|cell| cell.trim().replace('\n', " "))
+                    .collect::<Vec<String>>()
+                    .join("\t")
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+        _ => children_to_text(node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::document_to_markdown;
+    use crate::utils::article::plain_text;
+    use crate::utils::markdown::markdown_to_document;
+
+    fn round_trip(document: &Value) -> Value {
+        markdown_to_document(&document_to_markdown(document))
+    }
+
+    fn paragraph(children: Value) -> Value {
+        json!({ "children": [{ "name": "paragraph", "children": children }] })
+    }
+
+    #[test]
+    fn line_leading_markers_stay_text() {
+        for text in [
+            "- a",
+            "+ a",
+            "1. a",
+            "12) a",
+            "~~~ a",
+            "# a",
+            "> a",
+            "a\n===",
+            "a\n- b",
+            "a\n3. b",
+            "x <b> [c] *d* 1. e",
+        ] {
+            let document = round_trip(&paragraph(json!([{ "text": text }])));
+            let blocks = document["children"].as_array().unwrap();
+            assert_eq!(blocks.len(), 1, "{}", text);
+            assert_eq!(blocks[0]["name"], "paragraph", "{}", text);
+            assert_eq!(plain_text(&blocks[0]), text);
+        }
+    }
+
+    #[test]
+    fn link_and_image_urls_survive() {
+        for url in [
+            "https://sfx.xyz/a",
+            "/a b",
+            "/a(b",
+            "/wiki/A_(b)",
+            "/a\\b",
+            "/a<b>",
+        ] {
+            let document = round_trip(&paragraph(json!([
+                { "name": "link", "url": url, "children": [{ "text": "a" }] }
+            ])));
+            assert_eq!(document["children"][0]["children"][0]["url"], url);
+
+            let document = round_trip(&paragraph(json!([{ "text": "a", "link": url }])));
+            assert_eq!(document["children"][0]["children"][0]["url"], url);
+
+            let document = round_trip(&json!({ "children": [{
+                "name": "image",
+                "url": url,
+                "alt": "图 [1]",
+                "caption": "说明 \"引号\" \\",
+            }] }));
+            let image = &document["children"][0];
+            assert_eq!(image["name"], "image");
+            assert_eq!(image["url"], url);
+            assert_eq!(image["alt"], "图 [1]");
+            assert_eq!(image["caption"], "说明 \"引号\" \\");
+        }
+    }
+
+    #[test]
+    fn header_keeps_inline_marks() {
+        let document = json!({ "children": [{
+            "name": "header",
+            "header": 2,
+            "children": [
+                { "text": "a " },
+                { "text": "b", "bold": true },
+                { "text": "\n" },
+                { "text": "c", "code": true },
+            ],
+        }] });
+        assert_eq!(document_to_markdown(&document), "## a **b** `c`\n");
+    }
+}
@@ -0,0 +1,527 @@
+// 服务端的简易语法高亮，只区分关键字、字面量、字符串、注释和数字，
+// 样式由前端的fx-hl-*类提供
+
+pub struct Language {
+    pub name: &'static str,
+    aliases: &'static [&'static str],
+    keywords: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    case_insensitive: bool,
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "rust",
+        aliases: &["rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+            "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+            "type", "unsafe", "use", "where", "while",
+        ],
+        literals: &["true", "false", "None", "Some", "Ok", "Err"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"'],
+        case_insensitive: false,
+    },
+    Language {
+        name: "javascript",
+        aliases: &["js", "jsx", "typescript", "ts", "tsx"],
+        keywords: &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "export",
+            "extends",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "of",
+            "return",
+            "static",
+            "switch",
+            "this",
+            "throw",
+            "try",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "while",
+            "yield",
+        ],
+        literals: &["true", "false", "null", "undefined", "NaN"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        case_insensitive: false,
+    },
+    Language {
+        name: "python",
+        aliases: &["py"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+            "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+            "with", "yield",
+        ],
+        literals: &["True", "False", "None"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "go",
+        aliases: &["golang"],
+        keywords: &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "fallthrough",
+            "for",
+            "func",
+            "go",
+            "goto",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "type",
+            "var",
+        ],
+        literals: &["true", "false", "nil", "iota"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        case_insensitive: false,
+    },
+    Language {
+        name: "java",
+        aliases: &["kotlin", "kt"],
+        keywords: &[
+            "abstract",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "continue",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "extends",
+            "final",
+            "finally",
+            "for",
+            "fun",
+            "if",
+            "implements",
+            "import",
+            "instanceof",
+            "interface",
+            "new",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "super",
+            "switch",
+            "this",
+            "throw",
+            "throws",
+            "try",
+            "val",
+            "var",
+            "void",
+            "when",
+            "while",
+        ],
+        literals: &["true", "false", "null"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "cpp",
+        aliases: &["c", "c++", "cc", "h", "hpp"],
+        keywords: &[
+            "auto",
+            "break",
+            "case",
+            "char",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "double",
+            "else",
+            "enum",
+            "extern",
+            "float",
+            "for",
+            "if",
+            "include",
+            "int",
+            "long",
+            "namespace",
+            "new",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "short",
+            "signed",
+            "sizeof",
+            "static",
+            "struct",
+            "switch",
+            "template",
+            "this",
+            "typedef",
+            "typename",
+            "union",
+            "unsigned",
+            "using",
+            "virtual",
+            "void",
+            "while",
+        ],
+        literals: &["true", "false", "NULL", "nullptr"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "shell",
+        aliases: &["sh", "bash", "zsh", "console"],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "until", "while",
+        ],
+        literals: &["true", "false"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "sql",
+        aliases: &["postgresql", "psql"],
+        keywords: &[
+            "add",
+            "alter",
+            "and",
+            "as",
+            "asc",
+            "by",
+            "column",
+            "create",
+            "default",
+            "delete",
+            "desc",
+            "drop",
+            "exists",
+            "from",
+            "group",
+            "having",
+            "if",
+            "in",
+            "index",
+            "insert",
+            "into",
+            "is",
+            "join",
+            "key",
+            "left",
+            "limit",
+            "not",
+            "offset",
+            "on",
+            "or",
+            "order",
+            "primary",
+            "references",
+            "returning",
+            "select",
+            "set",
+            "table",
+            "update",
+            "values",
+            "where",
+        ],
+        literals: &["true", "false", "null"],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['\''],
+        case_insensitive: true,
+    },
+    Language {
+        name: "json",
+        aliases: &[],
+        keywords: &[],
+        literals: &["true", "false", "null"],
+        line_comments: &[],
+        block_comment: None,
+        quotes: &['"'],
+        case_insensitive: false,
+    },
+    Language {
+        name: "yaml",
+        aliases: &["yml", "toml"],
+        keywords: &[],
+        literals: &["true", "false", "null", "yes", "no"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "css",
+        aliases: &["scss", "less"],
+        keywords: &["important", "media", "import", "keyframes"],
+        literals: &[],
+        line_comments: &[],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        case_insensitive: false,
+    },
+    Language {
+        name: "html",
+        aliases: &["xml", "svg", "hbs", "handlebars"],
+        keywords: &[],
+        literals: &[],
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+        quotes: &['"'],
+        case_insensitive: false,
+    },
+];
+
+pub fn find_language(name: &str) -> Option<&'static Language> {
+    let name = name.trim().to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|language| language.name == name || language.aliases.contains(&name.as_str()))
+}
+
+fn starts_with_at(chars: &[char], index: usize, pattern: &str) -> bool {
+    pattern
+        .chars()
+        .enumerate()
+        .all(|(offset, c)| chars.get(index + offset) == Some(&c))
+}
+
+fn tokenize(language: &Language, chars: &[char]) -> Vec<(Option<&'static str>, String)> {
+    let mut tokens: Vec<(Option<&'static str>, String)> = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let mut class: Option<&'static str> = None;
+
+        if let Some((open, close)) = language.block_comment {
+            if starts_with_at(chars, i, open) {
+                i += open.chars().count();
+                while i < chars.len() && !starts_with_at(chars, i, close) {
+                    i += 1;
+                }
+                i = (i + close.chars().count()).min(chars.len());
+                class = Some("comment");
+            }
+        }
+        if class.is_none()
+            && language
+                .line_comments
+                .iter()
+                .any(|prefix| starts_with_at(chars, i, prefix))
+        {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            class = Some("comment");
+        }
+        if class.is_none() && language.quotes.contains(&chars[i]) {
+            let quote = chars[i];
+            i += 1;
+            while i < chars.len() && chars[i] != quote {
+                // 反引号以外的字符串不跨行
+                if chars[i] == '\n' && quote != '`' {
+                    break;
+                }
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            class = Some("string");
+        }
+        if class.is_none() && chars[i].is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            class = Some("number");
+        }
+        if class.is_none() && (chars[i].is_alphabetic() || chars[i] == '_') {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let matches = |words: &[&str]| {
+                if language.case_insensitive {
+                    words.iter().any(|w| w.eq_ignore_ascii_case(&word))
+                } else {
+                    words.contains(&word.as_str())
+                }
+            };
+            if matches(language.keywords) {
+                class = Some("keyword");
+            } else if matches(language.literals) {
+                class = Some("literal");
+            } else {
+                plain.push_str(&word);
+                continue;
+            }
+        }
+
+        match class {
+            Some(_) => {
+                if !plain.is_empty() {
+                    tokens.push((None, std::mem::take(&mut plain)));
+                }
+                tokens.push((class, chars[start..i].iter().collect()));
+            }
+            None => {
+                plain.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    if !plain.is_empty() {
+        tokens.push((None, plain));
+    }
+    tokens
+}
+
+/// 高亮源代码并按行返回HTML，跨行的token会在每一行内单独闭合，
+/// 未知的语言只做转义
+pub fn highlight_lines(language: Option<&Language>, source: &str) -> Vec<String> {
+    let chars: Vec<char> = source.chars().collect();
+    let tokens = match language {
+        Some(language) => tokenize(language, &chars),
+        None => vec![(None, source.to_string())],
+    };
+
+    let mut lines: Vec<String> = vec![String::new()];
+    for (class, text) in tokens {
+        for (index, part) in text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(String::new());
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let escaped = html_escape::encode_text(part);
+            let line = lines.last_mut().unwrap();
+            match class {
+                Some(class) => {
+                    line.push_str(&format!("<span class='fx-hl-{}'>{}</span>", class, escaped))
+                }
+                None => line.push_str(&escaped),
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_language, highlight_lines};
+
+    #[test]
+    fn escapes_html_inside_tokens() {
+        let lines = highlight_lines(find_language("rs"), "let s = \"<b>&\"; // a < b");
+        assert_eq!(
+            lines,
+            vec![
+                "<span class='fx-hl-keyword'>let</span> s = \
+                 <span class='fx-hl-string'>\"&lt;b&gt;&amp;\"</span>; \
+                 <span class='fx-hl-comment'>// a &lt; b</span>"
+            ]
+        );
+    }
+
+    #[test]
+    fn closes_block_comments_on_every_line() {
+        let lines = highlight_lines(find_language("rust"), "/* a\n\nb */ fn");
+        assert_eq!(
+            lines,
+            vec![
+                "<span class='fx-hl-comment'>/* a</span>",
+                "",
+                "<span class='fx-hl-comment'>b */</span> <span class='fx-hl-keyword'>fn</span>",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_template_strings_span_lines() {
+        let lines = highlight_lines(find_language("js"), "`a\nb` \"c\nd\"");
+        assert_eq!(
+            lines,
+            vec![
+                "<span class='fx-hl-string'>`a</span>",
+                "<span class='fx-hl-string'>b`</span> <span class='fx-hl-string'>\"c</span>",
+                "d<span class='fx-hl-string'>\"</span>",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_language_is_only_escaped() {
+        assert!(find_language("brainfuck").is_none());
+        assert_eq!(
+            highlight_lines(None, "if <x>\nfn"),
+            vec!["if &lt;x&gt;", "fn"]
+        );
+    }
+}
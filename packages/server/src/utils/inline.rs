@@ -0,0 +1,380 @@
+use serde_json::Value;
+
+use crate::utils::article::safe_url;
+
+/// 文本节点上的行内样式
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Marks {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strike: bool,
+    pub code: bool,
+    // 同时设置时只保留上标
+    pub superscript: bool,
+    pub subscript: bool,
+    pub highlight: bool,
+    // 高亮的背景色，未指定时使用默认样式
+    pub highlight_color: Option<String>,
+    pub color: Option<String>,
+    pub link: Option<String>,
+}
+
+// 标记可以是布尔值，也兼容旧数据中的非空字符串
+fn is_marked(value: &Value) -> bool {
+    match value {
+        Value::Bool(marked) => *marked,
+        Value::String(text) => !text.is_empty() && text != "false",
+        _ => false,
+    }
+}
+
+/// 只接受#rgb、#rrggbb、#rrggbbaa、rgb()/rgba()及颜色名，防止通过style注入其他样式
+pub fn safe_color(color: &str) -> Option<String> {
+    let color = color.trim();
+    let valid = if let Some(hex) = color.strip_prefix('#') {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else if let Some(args) = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))
+    {
+        args.strip_suffix(')').map_or(false, |args| {
+            args.chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, ',' | ' ' | '.' | '%'))
+        })
+    } else {
+        !color.is_empty() && color.len() <= 32 && color.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    if valid {
+        Some(color.to_lowercase())
+    } else {
+        None
+    }
+}
+
+// 旧数据中highlight可能是"true"/"false"字符串，只有其他字符串才作为背景色
+fn highlight_color(value: &Value) -> Option<String> {
+    match value.as_str() {
+        Some("true") | Some("false") => None,
+        Some(color) => safe_color(color),
+        None => None,
+    }
+}
+
+impl Marks {
+    pub fn from_node(node: &Value) -> Marks {
+        let superscript = is_marked(&node["superscript"]);
+        Marks {
+            bold: is_marked(&node["bold"]),
+            italic: is_marked(&node["italic"]),
+            underline: is_marked(&node["underline"]),
+            strike: is_marked(&node["strike"]),
+            code: is_marked(&node["code"]),
+            superscript,
+            subscript: !superscript && is_marked(&node["subscript"]),
+            highlight: is_marked(&node["highlight"]),
+            highlight_color: highlight_color(&node["highlight"]),
+            color: node["color"].as_str().and_then(safe_color),
+            link: node["link"].as_str().and_then(safe_url),
+        }
+    }
+
+    fn class_names(&self) -> Vec<&'static str> {
+        let mut class_names: Vec<&'static str> = Vec::new();
+        if self.bold {
+            class_names.push("fx-bold");
+        }
+        if self.italic {
+            class_names.push("fx-italic");
+        }
+        class_names
+    }
+
+    fn styles(&self) -> Vec<String> {
+        let mut decorations: Vec<&str> = Vec::new();
+        if self.underline {
+            decorations.push("underline");
+        }
+        if self.strike {
+            decorations.push("line-through");
+        }
+
+        let mut styles: Vec<String> = Vec::new();
+        if !decorations.is_empty() {
+            styles.push(format!("text-decoration:{}", decorations.join(" ")));
+        }
+        if let Some(color) = &self.color {
+            styles.push(format!("color:{}", color));
+        }
+        styles
+    }
+}
+
+// 从内到外依次为：带class和style的span、mark、sup/sub、code、a
+pub fn render_text(node: &Value) -> Result<String, String> {
+    let text = node["text"].as_str().ok_or_else(|| "未找到text属性")?;
+    let marks = Marks::from_node(node);
+
+    let mut attributes = String::new();
+    let class_names = marks.class_names();
+    if !class_names.is_empty() {
+        attributes.push_str(&format!(" class='{}'", class_names.join(" ")));
+    }
+    let styles = marks.styles();
+    if !styles.is_empty() {
+        attributes.push_str(&format!(" style='{}'", styles.join(";")));
+    }
+    let mut html = format!(
+        "<span{}>{}</span>",
+        attributes,
+        html_escape::encode_text(text)
+    );
+
+    if marks.highlight {
+        html = match &marks.highlight_color {
+            Some(color) => format!(
+                "<mark class='fx-highlight' style='background-color:{}'>{}</mark>",
+                color, html
+            ),
+            None => format!("<mark class='fx-highlight'>{}</mark>", html),
+        };
+    }
+    if marks.superscript {
+        html = format!("<sup>{}</sup>", html);
+    } else if marks.subscript {
+        html = format!("<sub>{}</sub>", html);
+    }
+    if marks.code {
+        html = format!("<code class='fx-code'>{}</code>", html);
+    }
+    if let Some(href) = &marks.link {
+        html = format!(
+            "<a class='fx-link' href='{}' rel='noopener noreferrer'>{}</a>",
+            href, html
+        );
+    }
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BOOLEAN_MARKS: [&str; 8] = [
+        "bold",
+        "italic",
+        "underline",
+        "strike",
+        "code",
+        "superscript",
+        "subscript",
+        "highlight",
+    ];
+
+    // 检查标签是否成对且正确嵌套，返回按出现顺序的开始标签名
+    fn assert_well_formed(html: &str) -> Vec<String> {
+        let mut stack: Vec<String> = Vec::new();
+        let mut opened: Vec<String> = Vec::new();
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..].find('>').expect("标签未闭合") + start;
+            let tag = &rest[start + 1..end];
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(stack.pop().as_deref(), Some(name), "标签嵌套错误: {}", html);
+            } else {
+                let name = tag.split(' ').next().unwrap().to_string();
+                assert!(!tag.contains("  "), "属性之间有多余的空格: {}", html);
+                assert!(!tag.ends_with(' '), "属性后有多余的空格: {}", html);
+                stack.push(name.clone());
+                opened.push(name);
+            }
+            rest = &rest[end + 1..];
+        }
+        assert!(stack.is_empty(), "存在未闭合的标签: {}", html);
+        opened
+    }
+
+    fn class_attribute(html: &str) -> Option<String> {
+        let start = html.find("<span class='")? + "<span class='".len();
+        let end = html[start..].find('\'')? + start;
+        Some(html[start..end].to_string())
+    }
+
+    #[test]
+    fn renders_plain_text() {
+        let html = render_text(&json!({ "text": "hello" })).unwrap();
+        assert_eq!(html, "<span>hello</span>");
+    }
+
+    #[test]
+    fn escapes_text() {
+        let html = render_text(&json!({ "text": "<b>&</b>" })).unwrap();
+        assert_eq!(html, "<span>&lt;b&gt;&amp;&lt;/b&gt;</span>");
+    }
+
+    #[test]
+    fn requires_text() {
+        assert!(render_text(&json!({ "bold": true })).is_err());
+    }
+
+    #[test]
+    fn renders_every_combination_of_boolean_marks() {
+        for mask in 0..(1u32 << BOOLEAN_MARKS.len()) {
+            let mut node = json!({ "text": "x" });
+            let enabled: Vec<&str> = BOOLEAN_MARKS
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| mask & (1 << index) != 0)
+                .map(|(_, mark)| *mark)
+                .collect();
+            for mark in &enabled {
+                node[*mark] = json!(true);
+            }
+            let html = render_text(&node).unwrap();
+            let tags = assert_well_formed(&html);
+            let has = |mark: &str| enabled.contains(&mark);
+
+            assert!(html.contains(">x<"), "{}", html);
+
+            let expected_classes: Vec<&str> = [("bold", "fx-bold"), ("italic", "fx-italic")]
+                .iter()
+                .filter(|(mark, _)| has(mark))
+                .map(|(_, class)| *class)
+                .collect();
+            match class_attribute(&html) {
+                Some(classes) => assert_eq!(classes, expected_classes.join(" "), "{}", html),
+                None => assert!(expected_classes.is_empty(), "{}", html),
+            }
+
+            let decorations: Vec<&str> = [("underline", "underline"), ("strike", "line-through")]
+                .iter()
+                .filter(|(mark, _)| has(mark))
+                .map(|(_, decoration)| *decoration)
+                .collect();
+            if decorations.is_empty() {
+                assert!(!html.contains("text-decoration"), "{}", html);
+            } else {
+                let style = format!("style='text-decoration:{}'", decorations.join(" "));
+                assert!(html.contains(&style), "{}", html);
+            }
+
+            let count = |name: &str| tags.iter().filter(|tag| *tag == name).count();
+            assert_eq!(count("span"), 1, "{}", html);
+            assert_eq!(count("code"), has("code") as usize, "{}", html);
+            assert_eq!(count("mark"), has("highlight") as usize, "{}", html);
+            assert_eq!(count("sup"), has("superscript") as usize, "{}", html);
+            assert_eq!(
+                count("sub"),
+                (has("subscript") && !has("superscript")) as usize,
+                "{}",
+                html
+            );
+            assert_eq!(count("a"), 0, "{}", html);
+        }
+    }
+
+    #[test]
+    fn renders_marks_in_fixed_order() {
+        let html = render_text(&json!({
+            "text": "x",
+            "bold": true,
+            "italic": true,
+            "underline": true,
+            "strike": true,
+            "code": true,
+            "superscript": true,
+            "highlight": true,
+            "color": "#ff0000",
+            "link": "https://sfx.xyz",
+        }))
+        .unwrap();
+        assert_eq!(
+            html,
+            "<a class='fx-link' href='https://sfx.xyz' rel='noopener noreferrer'>\
+<code class='fx-code'><sup><mark class='fx-highlight'>\
+<span class='fx-bold fx-italic' style='text-decoration:underline line-through;color:#ff0000'>x</span>\
+</mark></sup></code></a>"
+        );
+    }
+
+    #[test]
+    fn ignores_false_and_legacy_string_marks() {
+        let marks = Marks::from_node(&json!({
+            "text": "x",
+            "bold": false,
+            "italic": "true",
+            "underline": "",
+            "strike": "false",
+        }));
+        assert!(!marks.bold);
+        assert!(marks.italic);
+        assert!(!marks.underline);
+        assert!(!marks.strike);
+    }
+
+    #[test]
+    fn renders_color() {
+        let html = render_text(&json!({ "text": "x", "color": "Red" })).unwrap();
+        assert_eq!(html, "<span style='color:red'>x</span>");
+
+        let html = render_text(&json!({ "text": "x", "color": "rgba(0, 0, 0, 0.5)" })).unwrap();
+        assert_eq!(html, "<span style='color:rgba(0, 0, 0, 0.5)'>x</span>");
+    }
+
+    #[test]
+    fn rejects_unsafe_color() {
+        for color in [
+            "red;background:url(x)",
+            "#12345",
+            "#zzz",
+            "expression(alert(1))",
+            "'",
+        ] {
+            let html = render_text(&json!({ "text": "x", "color": color })).unwrap();
+            assert_eq!(html, "<span>x</span>", "{}", color);
+        }
+    }
+
+    #[test]
+    fn renders_highlight_color() {
+        let html = render_text(&json!({ "text": "x", "highlight": "#ff0" })).unwrap();
+        assert_eq!(
+            html,
+            "<mark class='fx-highlight' style='background-color:#ff0'><span>x</span></mark>"
+        );
+    }
+
+    #[test]
+    fn treats_legacy_highlight_string_as_plain_mark() {
+        for value in [json!(true), json!("true")] {
+            let html = render_text(&json!({ "text": "x", "highlight": value })).unwrap();
+            assert_eq!(html, "<mark class='fx-highlight'><span>x</span></mark>");
+        }
+        let marks = Marks::from_node(&json!({ "text": "x", "highlight": "false" }));
+        assert!(!marks.highlight);
+        assert_eq!(marks.highlight_color, None);
+    }
+
+    #[test]
+    fn renders_link() {
+        let html = render_text(&json!({ "text": "x", "link": "/article/read/1" })).unwrap();
+        assert_eq!(
+            html,
+            "<a class='fx-link' href='/article/read/1' rel='noopener noreferrer'><span>x</span></a>"
+        );
+    }
+
+    #[test]
+    fn rejects_unsafe_link() {
+        let html = render_text(&json!({ "text": "x", "link": "javascript:alert(1)" })).unwrap();
+        assert_eq!(html, "<span>x</span>");
+    }
+
+    #[test]
+    fn escapes_link_quotes() {
+        let html = render_text(&json!({ "text": "x", "link": "/a'b" })).unwrap();
+        assert_well_formed(&html);
+        assert!(!html.contains("/a'b"), "{}", html);
+    }
+}
@@ -0,0 +1,307 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use serde_json::{json, Value};
+
+use crate::utils::article::plain_text;
+
+// 将Markdown转换为文档模型，节点结构与utils::article中的渲染器一致
+pub fn markdown_to_document(source: &str) -> Value {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut builder = DocumentBuilder::new();
+    for event in Parser::new_ext(source, options) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+struct DocumentBuilder {
+    // 栈底为文档本身，栈中的每个节点都有children
+    stack: Vec<Value>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    // 代码块中的文本，代码块结束时按行拆分
+    code: Option<String>,
+    in_table_head: bool,
+}
+
+impl DocumentBuilder {
+    fn new() -> DocumentBuilder {
+        DocumentBuilder {
+            stack: vec![json!({ "children": [] })],
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            code: None,
+            in_table_head: false,
+        }
+    }
+
+    fn finish(mut self) -> Value {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        self.stack.pop().unwrap()
+    }
+
+    fn open(&mut self, node: Value) {
+        self.stack.push(node);
+    }
+
+    fn close(&mut self) {
+        if self.stack.len() < 2 {
+            return;
+        }
+        let node = self.stack.pop().unwrap();
+        self.append(node);
+    }
+
+    fn append(&mut self, node: Value) {
+        if let Some(children) = self
+            .stack
+            .last_mut()
+            .and_then(|parent| parent["children"].as_array_mut())
+        {
+            children.push(node);
+        }
+    }
+
+    fn text(&mut self, text: &str, code: bool) {
+        let mut leaf = json!({ "text": text });
+        if self.bold > 0 {
+            leaf["bold"] = json!(true);
+        }
+        if self.italic > 0 {
+            leaf["italic"] = json!(true);
+        }
+        if self.strike > 0 {
+            leaf["strike"] = json!(true);
+        }
+        if code {
+            leaf["code"] = json!(true);
+        }
+        self.append(leaf);
+    }
+
+    fn event(&mut self, event: Event) {
+        if let Some(code) = self.code.as_mut() {
+            match event {
+                Event::Text(text) => {
+                    code.push_str(&text);
+                    return;
+                }
+                Event::End(Tag::CodeBlock(_)) => {}
+                _ => return,
+            }
+        }
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.text(&text, false),
+            Event::Code(text) => self.text(&text, true),
+            // 不信任Markdown中的HTML，按普通文本处理
+            Event::Html(html) => self.text(&html, false),
+            Event::SoftBreak => self.text(" ", false),
+            Event::HardBreak => self.text("\n", false),
+            Event::Rule => self.append(json!({ "name": "hr", "children": [] })),
+            Event::TaskListMarker(checked) => {
+                self.text(if checked { "[x] " } else { "[ ] " }, false)
+            }
+            Event::FootnoteReference(name) => self.text(&format!("[^{}]", name), false),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.open(json!({ "name": "paragraph", "children": [] })),
+            Tag::Heading(level, _, _) => self.open(json!({
+                "name": "header",
+                "header": heading_level(level),
+                "children": [],
+            })),
+            Tag::BlockQuote => self.open(json!({ "name": "block-quote", "children": [] })),
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .next()
+                        .unwrap_or("")
+                        .to_string(),
+                    CodeBlockKind::Indented => "".to_string(),
+                };
+                self.open(json!({ "name": "code-block", "language": language, "children": [] }));
+                self.code = Some(String::new());
+            }
+            Tag::List(Some(start)) => self.open(json!({
+                "name": "numbered-list",
+                "start": start,
+                "children": [],
+            })),
+            Tag::List(None) => self.open(json!({ "name": "bulleted-list", "children": [] })),
+            Tag::Item => self.open(json!({ "name": "list-item", "children": [] })),
+            Tag::FootnoteDefinition(name) => self.open(json!({
+                "name": "footnote",
+                "label": name.to_string(),
+                "children": [],
+            })),
+            Tag::Table(_) => self.open(json!({ "name": "table", "children": [] })),
+            // 表头的单元格直接位于TableHead中，没有TableRow
+            Tag::TableHead => {
+                self.in_table_head = true;
+                self.open(json!({ "name": "table-row", "children": [] }));
+            }
+            Tag::TableRow => self.open(json!({ "name": "table-row", "children": [] })),
+            Tag::TableCell => self.open(json!({
+                "name": "table-cell",
+                "header": self.in_table_head,
+                "children": [],
+            })),
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link(_, url, _) => self.open(json!({
+                "name": "link",
+                "url": url.to_string(),
+                "children": [],
+            })),
+            Tag::Image(_, url, title) => self.open(json!({
+                "name": "image",
+                "url": url.to_string(),
+                "caption": title.to_string(),
+                "children": [],
+            })),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Emphasis => self.italic = self.italic.saturating_sub(1),
+            Tag::Strong => self.bold = self.bold.saturating_sub(1),
+            Tag::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            Tag::CodeBlock(_) => {
+                let code = self.code.take().unwrap_or_default();
+                if let Some(node) = self.stack.last_mut() {
+                    node["children"] = Value::Array(
+                        code.trim_end_matches('\n')
+                            .split('\n')
+                            .map(|line| {
+                                json!({ "name": "code-line", "children": [{ "text": line }] })
+                            })
+                            .collect(),
+                    );
+                }
+                self.close();
+            }
+            Tag::Image(..) => {
+                if let Some(node) = self.stack.last_mut() {
+                    let alt = plain_text(node);
+                    node["alt"] = json!(alt);
+                    node["children"] = json!([]);
+                }
+                self.close();
+            }
+            Tag::TableHead => {
+                self.in_table_head = false;
+                self.close();
+            }
+            Tag::Paragraph => {
+                // 只包含一张图片的段落直接作为图片节点
+                if self.stack.len() < 2 {
+                    return;
+                }
+                let node = self.stack.pop().unwrap();
+                let children = node["children"].as_array().cloned().unwrap_or_default();
+                if children.len() == 1 && children[0]["name"] == "image" {
+                    self.append(children[0].clone());
+                } else {
+                    self.append(node);
+                }
+            }
+            _ => self.close(),
+        }
+    }
+}
+
+fn heading_level(level: HeadingLevel) -> i64 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::markdown_to_document;
+
+    #[test]
+    fn nested_lists() {
+        let document = markdown_to_document("- a\n  1. b\n  2. c\n- d\n");
+        let list = &document["children"][0];
+        assert_eq!(list["name"], "bulleted-list");
+        assert_eq!(list["children"].as_array().unwrap().len(), 2);
+        let nested = &list["children"][0]["children"][1];
+        assert_eq!(nested["name"], "numbered-list");
+        assert_eq!(nested["start"], 1);
+        assert_eq!(nested["children"][1]["children"][0]["text"], "c");
+    }
+
+    #[test]
+    fn fenced_code_with_info_string() {
+        let document = markdown_to_document("```rust,ignore title=a\nfn a() {}\n\n}\n```\n");
+        assert_eq!(
+            document["children"][0],
+            json!({
+                "name": "code-block",
+                "language": "rust",
+                "children": [
+                    { "name": "code-line", "children": [{ "text": "fn a() {}" }] },
+                    { "name": "code-line", "children": [{ "text": "" }] },
+                    { "name": "code-line", "children": [{ "text": "}" }] },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let document = markdown_to_document("| a | b |\n| --- | --- |\n| 1 | **2** |\n");
+        let table = &document["children"][0];
+        assert_eq!(table["name"], "table");
+        let rows = table["children"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["children"][1]["header"], true);
+        assert_eq!(rows[0]["children"][1]["children"][0]["text"], "b");
+        assert_eq!(rows[1]["children"][1]["header"], false);
+        assert_eq!(
+            rows[1]["children"][1]["children"][0],
+            json!({ "text": "2", "bold": true })
+        );
+    }
+
+    #[test]
+    fn emphasis_in_headings() {
+        let document = markdown_to_document("## a *b* `c`\n");
+        assert_eq!(
+            document["children"][0],
+            json!({
+                "name": "header",
+                "header": 2,
+                "children": [
+                    { "text": "a " },
+                    { "text": "b", "italic": true },
+                    { "text": " " },
+                    { "text": "c", "code": true },
+                ],
+            })
+        );
+    }
+}
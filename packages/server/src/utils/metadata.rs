@@ -0,0 +1,161 @@
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::ProximaConfig;
+use crate::models::account::AccountModel;
+use crate::models::article::ArticleModel;
+use crate::utils;
+use crate::utils::article::image_urls;
+
+/// 页面的OpenGraph、Twitter card及JSON-LD数据，由partial/meta.hbs输出
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PageMeta {
+    pub title: String,
+    pub description: String,
+    pub canonical: String,
+    // website、article或profile
+    pub og_type: String,
+    pub image: String,
+    pub site_name: String,
+    pub twitter_card: String,
+    pub published_time: Option<String>,
+    pub modified_time: Option<String>,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    pub json_ld: String,
+}
+
+fn rfc3339(time: &NaiveDateTime) -> String {
+    Utc.from_utc_datetime(time).to_rfc3339()
+}
+
+// JSON-LD放在script标签中，需要避免内容中出现</script>
+fn to_json_ld(value: &Value) -> String {
+    value
+        .to_string()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+}
+
+fn twitter_card(image: &str) -> String {
+    if image.is_empty() {
+        "summary".to_string()
+    } else {
+        "summary_large_image".to_string()
+    }
+}
+
+fn person(config: &ProximaConfig, account: &AccountModel) -> Value {
+    let mut person = json!({
+        "@type": "Person",
+        "name": account.nickname,
+        "url": format!("{}/user/{}", config.site_url, account.pk),
+        "image": utils::get_photo_or_default(config, &account.photo),
+    });
+    if !account.description.is_empty() {
+        person["description"] = json!(account.description);
+    }
+    if !account.site.is_empty() {
+        person["sameAs"] = json!([account.site]);
+    }
+    person
+}
+
+impl PageMeta {
+    // path为站内路径，如/about
+    pub fn website(config: &ProximaConfig, title: &str, path: &str, description: &str) -> PageMeta {
+        let canonical = format!("{}{}", config.site_url, path);
+        PageMeta {
+            title: title.to_string(),
+            description: description.to_string(),
+            og_type: "website".to_string(),
+            site_name: config.site_name.clone(),
+            twitter_card: twitter_card(""),
+            json_ld: to_json_ld(&json!({
+                "@context": "https://schema.org",
+                "@type": "WebSite",
+                "name": config.site_name,
+                "url": config.site_url,
+            })),
+            canonical,
+            ..PageMeta::default()
+        }
+    }
+
+    pub fn article(
+        config: &ProximaConfig,
+        article: &ArticleModel,
+        creator: &AccountModel,
+    ) -> PageMeta {
+        let canonical = format!("{}{}", config.site_url, article.path());
+        let description = article.summary();
+        let image = image_urls(&config.site_url, &article.body)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let tags = article.keyword_list();
+
+        let mut posting = json!({
+            "@context": "https://schema.org",
+            "@type": "BlogPosting",
+            "headline": article.title,
+            "description": description,
+            "url": canonical,
+            "mainEntityOfPage": {
+                "@type": "WebPage",
+                "@id": canonical,
+            },
+            "datePublished": rfc3339(&article.create_time),
+            "dateModified": rfc3339(&article.update_time),
+            "wordCount": article.reading_stats().word_count,
+            "keywords": tags.join(","),
+            "author": person(config, creator),
+            "publisher": {
+                "@type": "Organization",
+                "name": config.site_name,
+                "url": config.site_url,
+            },
+        });
+        if !image.is_empty() {
+            posting["image"] = json!([image]);
+        }
+
+        PageMeta {
+            title: article.title.clone(),
+            description,
+            og_type: "article".to_string(),
+            twitter_card: twitter_card(&image),
+            site_name: config.site_name.clone(),
+            published_time: Some(rfc3339(&article.create_time)),
+            modified_time: Some(rfc3339(&article.update_time)),
+            author: Some(format!("{}/user/{}", config.site_url, creator.pk)),
+            tags,
+            json_ld: to_json_ld(&posting),
+            canonical,
+            image,
+        }
+    }
+
+    pub fn profile(config: &ProximaConfig, account: &AccountModel) -> PageMeta {
+        let canonical = format!("{}/user/{}", config.site_url, account.pk);
+        let image = utils::get_photo_or_default(config, &account.photo);
+        PageMeta {
+            title: account.nickname.clone(),
+            description: account.description.clone(),
+            og_type: "profile".to_string(),
+            // 头像不适合大图卡片
+            twitter_card: twitter_card(""),
+            site_name: config.site_name.clone(),
+            json_ld: to_json_ld(&json!({
+                "@context": "https://schema.org",
+                "@type": "ProfilePage",
+                "url": canonical,
+                "mainEntity": person(config, account),
+            })),
+            canonical,
+            image,
+            ..PageMeta::default()
+        }
+    }
+}
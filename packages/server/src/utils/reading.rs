@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::article::plain_text;
+use crate::utils::export;
+
+// 中文按字计算阅读速度，其他语言按词计算
+const CJK_CHARS_PER_MINUTE: usize = 300;
+const WORDS_PER_MINUTE: usize = 200;
+// 摘要达到这个长度后不再继续取后面的段落
+const EXCERPT_MIN_CHARS: usize = 80;
+const EXCERPT_MAX_CHARS: usize = 160;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadingStats {
+    // 中日韩文字每个字计为一个词
+    pub word_count: i32,
+    pub reading_minutes: i32,
+    pub excerpt: String,
+}
+
+impl ReadingStats {
+    pub fn from_document(document: &Value) -> ReadingStats {
+        let text = export::document_to_text(document);
+        let (cjk_chars, words) = count_words(&text);
+        let minutes = if cjk_chars + words == 0 {
+            0
+        } else {
+            let seconds = cjk_chars * 60 / CJK_CHARS_PER_MINUTE + words * 60 / WORDS_PER_MINUTE;
+            ((seconds + 59) / 60).max(1)
+        };
+        ReadingStats {
+            word_count: (cjk_chars + words) as i32,
+            reading_minutes: minutes as i32,
+            excerpt: build_excerpt(document),
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // 平假名、片假名
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xAC00..=0xD7AF // 谚文
+        | 0xF900..=0xFAFF
+        | 0x20000..=0x2FA1F)
+}
+
+// 返回中日韩文字的字数和其他文字的词数
+fn count_words(text: &str) -> (usize, usize) {
+    let mut cjk_chars = 0;
+    let mut words = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk_chars += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                words += 1;
+            }
+            in_word = true;
+        } else if !(in_word && matches!(c, '\'' | '-' | '_')) {
+            in_word = false;
+        }
+    }
+    (cjk_chars, words)
+}
+
+// 取开头的几个段落，超出长度时截断并加上省略号
+fn build_excerpt(document: &Value) -> String {
+    let mut excerpt = String::new();
+    for node in document["children"].as_array().unwrap_or(&Vec::new()) {
+        if node["name"].as_str() != Some("paragraph") {
+            continue;
+        }
+        let text = plain_text(node)
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        if !excerpt.is_empty() {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(&text);
+        if excerpt.chars().count() >= EXCERPT_MIN_CHARS {
+            break;
+        }
+    }
+    if excerpt.chars().count() <= EXCERPT_MAX_CHARS {
+        return excerpt;
+    }
+    let mut truncated: String = excerpt.chars().take(EXCERPT_MAX_CHARS).collect();
+    truncated = truncated.trim_end().to_string();
+    truncated.push('…');
+    truncated
+}
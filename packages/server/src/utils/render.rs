@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::utils::article::{self, Toc};
+use crate::utils::inline;
+
+/// 渲染文档中指定name的节点，返回HTML
+pub trait NodeRenderer: Send + Sync {
+    fn render(&self, ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String>;
+}
+
+impl<F> NodeRenderer for F
+where
+    F: Fn(&mut RenderContext, &serde_json::Value) -> Result<String, String> + Send + Sync,
+{
+    fn render(&self, ctx: &mut RenderContext, node: &serde_json::Value) -> Result<String, String> {
+        self(ctx, node)
+    }
+}
+
+/// 渲染过程中的上下文，自定义渲染器可以通过它渲染子节点及收集目录
+pub struct RenderContext<'a> {
+    registry: &'a RendererRegistry,
+    pub toc: &'a mut Toc,
+}
+
+impl<'a> RenderContext<'a> {
+    pub fn new(registry: &'a RendererRegistry, toc: &'a mut Toc) -> RenderContext<'a> {
+        RenderContext { registry, toc }
+    }
+
+    // 文本节点没有name，统一按文本渲染
+    pub fn render_node(&mut self, node: &serde_json::Value) -> Result<String, String> {
+        if node["text"].is_string() {
+            return inline::render_text(node);
+        }
+        let name = node["name"].as_str().ok_or_else(|| "未找到name属性")?;
+        let registry = self.registry;
+        match registry.get(name) {
+            Some(renderer) => renderer.render(self, node),
+            None => {
+                tracing::warn!("未知的节点类型: {}", name);
+                Ok(article::build_fallback(node))
+            }
+        }
+    }
+
+    // 单个节点出错时只记录警告，不影响整篇文章的渲染
+    pub fn render_block(&mut self, node: &serde_json::Value) -> String {
+        match self.render_node(node) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::warn!("节点渲染失败: {}, node: {}", err, node);
+                article::build_fallback(node)
+            }
+        }
+    }
+
+    // 子节点既可以是文本节点，也可以是段落、列表等块级节点
+    pub fn render_children(&mut self, node: &serde_json::Value) -> Result<String, String> {
+        let children = node["children"]
+            .as_array()
+            .ok_or_else(|| "children未定义")?;
+
+        let mut html = String::new();
+        for child in children {
+            html.push_str(&self.render_block(child));
+        }
+        Ok(html)
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct RendererRegistry {
+    renderers: HashMap<String, Arc<dyn NodeRenderer>>,
+}
+
+impl Debug for RendererRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.renderers.keys().collect();
+        names.sort();
+        f.debug_struct("RendererRegistry")
+            .field("renderers", &names)
+            .finish()
+    }
+}
+
+impl RendererRegistry {
+    pub fn new() -> RendererRegistry {
+        RendererRegistry::default()
+    }
+
+    /// 包含所有内置节点的渲染器
+    pub fn with_defaults() -> RendererRegistry {
+        let mut registry = RendererRegistry::new();
+        article::register_defaults(&mut registry);
+        registry
+    }
+
+    // 同名的渲染器会被覆盖，可以用来替换内置的实现
+    pub fn register<R>(&mut self, name: &str, renderer: R) -> &mut RendererRegistry
+    where
+        R: NodeRenderer + 'static,
+    {
+        self.renderers.insert(name.to_string(), Arc::new(renderer));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn NodeRenderer>> {
+        self.renderers.get(name).cloned()
+    }
+
+    pub fn build_body(
+        &self,
+        toc: &mut Toc,
+        document: &serde_json::Value,
+    ) -> Result<String, String> {
+        let mut ctx = RenderContext::new(self, toc);
+        ctx.render_children(document)
+    }
+}
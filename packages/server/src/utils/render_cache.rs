@@ -0,0 +1,99 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+use serde::Serialize;
+
+use crate::utils::article::TocItem;
+
+/// 内置渲染器或清理规则的输出变化时递增，使旧的缓存失效
+pub const RENDERER_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct RenderedArticle {
+    pub body_html: String,
+    pub title_id: String,
+    pub toc: Vec<TocItem>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct RenderCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+/// 按文章pk、内容哈希及渲染器版本缓存渲染结果，capacity为0时不缓存
+#[derive(Debug)]
+pub struct RenderCache {
+    entries: Mutex<LruCache<String, Arc<RenderedArticle>>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RenderCache {
+    pub fn new(capacity: usize) -> RenderCache {
+        RenderCache {
+            entries: Mutex::new(LruCache::new(capacity)),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    // content包含标题、正文以及影响输出的配置
+    pub fn key<T: Hash>(pk: &str, content: &T) -> String {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        format!("{}:{}:{:016x}", pk, RENDERER_VERSION, hasher.finish())
+    }
+
+    pub fn get_or_render<F>(&self, key: &str, render: F) -> Result<Arc<RenderedArticle>, String>
+    where
+        F: FnOnce() -> Result<RenderedArticle, String>,
+    {
+        if let Some(rendered) = self.entries.lock().unwrap().get(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(rendered.clone());
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // 渲染时不持有锁，并发的未命中可能重复渲染，结果相同
+        let rendered = Arc::new(render()?);
+        if self.capacity == 0 {
+            return Ok(rendered);
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .put(key.to_string(), rendered.clone());
+        Ok(rendered)
+    }
+
+    // 移除文章的所有缓存，文章更新后调用
+    pub fn invalidate(&self, pk: &str) {
+        let prefix = format!("{}:", pk);
+        let mut entries = self.entries.lock().unwrap();
+        let keys: Vec<String> = entries
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in keys {
+            entries.pop(&key);
+        }
+    }
+
+    pub fn stats(&self) -> RenderCacheStats {
+        RenderCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+            capacity: self.capacity,
+        }
+    }
+}
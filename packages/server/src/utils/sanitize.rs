@@ -0,0 +1,230 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ammonia::{Builder, Url, UrlRelative};
+
+use crate::config::ProximaConfig;
+use crate::utils::inline::safe_color;
+
+// 内置渲染器会输出的标签，自定义渲染器需要其他标签时通过SANITIZE_TAGS配置
+const TAGS: &[&str] = &[
+    "a",
+    "blockquote",
+    "br",
+    "code",
+    "div",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "img",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "span",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+const CLEAN_CONTENT_TAGS: &[&str] = &["script", "style"];
+
+const GENERIC_ATTRIBUTES: &[&str] = &["class", "style", "aria-hidden", "hidden"];
+
+const TAG_ATTRIBUTES: &[(&str, &str)] = &[
+    ("a", "href"),
+    ("img", "src"),
+    ("img", "alt"),
+    ("img", "loading"),
+    ("ol", "start"),
+    ("figure", "data-language"),
+    ("span", "data-line"),
+    ("h1", "id"),
+    ("h2", "id"),
+    ("h3", "id"),
+    ("h4", "id"),
+    ("h5", "id"),
+    ("h6", "id"),
+];
+
+const EXTERNAL_LINK_REL: &str = "nofollow noopener";
+
+/// 清理渲染后的文章正文，只保留白名单中的标签和属性，并为站外链接加上rel
+pub fn sanitize_html(config: &ProximaConfig, html: &str) -> String {
+    let mut tags: HashSet<&str> = TAGS.iter().copied().collect();
+    tags.extend(config.sanitize_tags.iter().map(|tag| tag.as_str()));
+    // script和style的内容会被整体移除，不能出现在白名单中
+    tags.retain(|tag| !CLEAN_CONTENT_TAGS.contains(tag));
+
+    let mut generic_attributes: HashSet<&str> = GENERIC_ATTRIBUTES.iter().copied().collect();
+    let mut tag_attributes: HashMap<&str, HashSet<&str>> = HashMap::new();
+    let extra_attributes = config
+        .sanitize_attributes
+        .iter()
+        .map(|(tag, attribute)| (tag.as_str(), attribute.as_str()));
+    for (tag, attribute) in TAG_ATTRIBUTES.iter().copied().chain(extra_attributes) {
+        if tag == "*" {
+            generic_attributes.insert(attribute);
+        } else {
+            tag_attributes.entry(tag).or_default().insert(attribute);
+        }
+    }
+
+    let url_schemes: HashSet<&str> = config
+        .sanitize_url_schemes
+        .iter()
+        .map(|scheme| scheme.as_str())
+        .collect();
+
+    let site_url = Url::parse(&config.site_url).ok();
+    // link_rel会在<a>已有属性的末尾追加rel，attribute_filter按顺序处理属性，
+    // 先记录href是否指向站外，处理到rel时再决定是否保留
+    let external = AtomicBool::new(false);
+
+    Builder::empty()
+        .tags(tags)
+        .clean_content_tags(CLEAN_CONTENT_TAGS.iter().copied().collect())
+        .generic_attributes(generic_attributes)
+        .tag_attributes(tag_attributes)
+        .url_schemes(url_schemes)
+        .url_relative(UrlRelative::PassThrough)
+        .link_rel(Some(EXTERNAL_LINK_REL))
+        .strip_comments(true)
+        .attribute_filter(
+            move |element, attribute, value| match (element, attribute) {
+                (_, "style") => sanitize_style(value).map(Cow::Owned),
+                ("a", "href") => {
+                    external.store(is_external(value, site_url.as_ref()), Ordering::Relaxed);
+                    Some(Cow::Borrowed(value))
+                }
+                ("a", "rel") => external
+                    .swap(false, Ordering::Relaxed)
+                    .then(|| Cow::Borrowed(value)),
+                _ => Some(Cow::Borrowed(value)),
+            },
+        )
+        .clean(html)
+        .to_string()
+}
+
+// style只允许颜色和text-decoration，其余声明直接丢弃
+fn sanitize_style(style: &str) -> Option<String> {
+    let mut declarations: Vec<String> = Vec::new();
+    for declaration in style.split(';') {
+        let (property, value) = match declaration.split_once(':') {
+            Some((property, value)) => (property.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        let value = match property.as_str() {
+            "color" | "background-color" => safe_color(value),
+            "text-decoration" => value
+                .split_whitespace()
+                .all(|part| matches!(part, "underline" | "line-through" | "none"))
+                .then(|| value.to_lowercase()),
+            _ => None,
+        };
+        if let Some(value) = value {
+            declarations.push(format!("{}:{}", property, value));
+        }
+    }
+    if declarations.is_empty() {
+        None
+    } else {
+        Some(declarations.join(";"))
+    }
+}
+
+// 相对地址按站点地址解析，解析后主机不同的才算站外链接
+fn is_external(href: &str, site_url: Option<&Url>) -> bool {
+    let url = match site_url {
+        Some(site_url) => site_url.join(href),
+        None => Url::parse(href),
+    };
+    match url {
+        Ok(url) => {
+            url.host_str().is_some()
+                && url.host_str() != site_url.and_then(|site_url| site_url.host_str())
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_html;
+    use crate::config::ProximaConfig;
+
+    fn config(extra: &str) -> ProximaConfig {
+        ProximaConfig::parse_config(&format!(
+            "STORAGE=memory\nTOTP_SECRET=totp\nJWT_KEY=secret\nSITE_URL=https://sfx.xyz\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_only_allowed_tags_and_attributes() {
+        let html = sanitize_html(
+            &config(""),
+            "<p onclick=\"x()\" class=\"a\">文字<script>alert(1)</script></p>\
+             <iframe src=\"https://sfx.xyz\"></iframe><h2 id=\"t\" title=\"t\">标题</h2>",
+        );
+        assert_eq!(html, "<p class=\"a\">文字</p><h2 id=\"t\">标题</h2>");
+    }
+
+    #[test]
+    fn extra_tags_and_attributes_from_config() {
+        let html = sanitize_html(
+            &config("SANITIZE_TAGS=details\nSANITIZE_ATTRIBUTES=details:open"),
+            "<details open=\"\" data-x=\"1\">内容</details>",
+        );
+        assert_eq!(html, "<details open=\"\">内容</details>");
+    }
+
+    #[test]
+    fn strips_unsafe_urls_and_styles() {
+        let html = sanitize_html(
+            &config(""),
+            "<a href=\"javascript:alert(1)\">a</a>\
+             <img src=\"data:image/png;base64,AAAA\">\
+             <span style=\"color:red;position:fixed\">b</span>",
+        );
+        assert_eq!(html, "<a>a</a><img><span style=\"color:red\">b</span>");
+    }
+
+    #[test]
+    fn adds_rel_only_to_external_links() {
+        let html = sanitize_html(
+            &config(""),
+            "<a href=\"/article/a\">1</a>\
+             <a href=\"https://sfx.xyz/b?x=1&amp;y=2\">2</a>\
+             <a href=\"https://example.com/\">3</a>\
+             <a href=\"//example.com/\">4</a>\
+             <a href=\"mailto:a@sfx.xyz\">5</a>\
+             <a>6</a>",
+        );
+        assert_eq!(
+            html,
+            "<a href=\"/article/a\">1</a>\
+             <a href=\"https://sfx.xyz/b?x=1&amp;y=2\">2</a>\
+             <a href=\"https://example.com/\" rel=\"nofollow noopener\">3</a>\
+             <a href=\"//example.com/\" rel=\"nofollow noopener\">4</a>\
+             <a href=\"mailto:a@sfx.xyz\">5</a>\
+             <a>6</a>"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use pinyin::ToPinyin;
+
+// 文章slug的最大长度，超出时在连字符处截断
+const ARTICLE_SLUG_MAX_LEN: usize = 80;
+
+// 保留字母、数字及中日韩文字，其余字符（空格、标点，包括中文标点）都视为分隔符
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut separator = false;
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() {
+            if separator && !slug.is_empty() {
+                slug.push('-');
+            }
+            separator = false;
+            slug.extend(c.to_lowercase());
+        } else {
+            separator = true;
+        }
+    }
+    slug
+}
+
+/// 同一篇文章内的slug去重，重复的依次加上-2、-3
+#[derive(Debug, Default)]
+pub struct SlugSet {
+    used: HashSet<String>,
+    // 每个基础slug下一次尝试的序号
+    counters: HashMap<String, usize>,
+}
+
+impl SlugSet {
+    pub fn new() -> SlugSet {
+        SlugSet::default()
+    }
+
+    pub fn unique(&mut self, text: &str) -> String {
+        let mut base = slugify(text);
+        if base.is_empty() {
+            base = "section".to_string();
+        }
+        let mut slug = base.clone();
+        let counter = self.counters.entry(base.clone()).or_insert(1);
+        while self.used.contains(&slug) {
+            *counter += 1;
+            slug = format!("{}-{}", base, counter);
+        }
+        self.used.insert(slug.clone());
+        slug
+    }
+}
+
+// 汉字转为不带声调的拼音，每个字单独成词，其余字符保持不变
+pub fn transliterate(text: &str) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        match c.to_pinyin() {
+            Some(pinyin) => {
+                result.push(' ');
+                result.push_str(pinyin.plain());
+                result.push(' ');
+            }
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+/// 文章地址中的slug，汉字转为拼音，只保留ASCII字母和数字，无法转换时为空
+pub fn article_slug(title: &str) -> String {
+    let mut slug = String::new();
+    let mut separator = false;
+    for c in transliterate(title).chars() {
+        if c.is_ascii_alphanumeric() {
+            if separator && !slug.is_empty() {
+                slug.push('-');
+            }
+            separator = false;
+            slug.push(c.to_ascii_lowercase());
+        } else {
+            separator = true;
+        }
+    }
+    if slug.len() > ARTICLE_SLUG_MAX_LEN {
+        slug.truncate(ARTICLE_SLUG_MAX_LEN);
+        if let Some(index) = slug.rfind('-') {
+            slug.truncate(index);
+        }
+    }
+    slug
+}
+
+// slug是base本身，或者是去重时加上-2、-3后的结果
+pub fn matches_base(slug: &str, base: &str) -> bool {
+    if slug == base {
+        return true;
+    }
+    slug.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .map_or(false, |counter| {
+            !counter.is_empty() && counter.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlugSet;
+
+    #[test]
+    fn duplicate_slugs_get_suffixes() {
+        let mut slugs = SlugSet::new();
+        assert_eq!(slugs.unique("简介"), "简介");
+        assert_eq!(slugs.unique("A 2"), "a-2");
+        assert_eq!(slugs.unique("a"), "a");
+        // a-2已被占用，跳过
+        assert_eq!(slugs.unique("A"), "a-3");
+        assert_eq!(slugs.unique("a!"), "a-4");
+        assert_eq!(slugs.unique("简介"), "简介-2");
+        assert_eq!(slugs.unique("？"), "section");
+        assert_eq!(slugs.unique(""), "section-2");
+    }
+}
@@ -1,8 +1,12 @@
 use crate::handlers::State;
-use crate::models::claims::Claims;
-use crate::models::error::{AppError, OtherError};
+use crate::models::article::{ArticleModel, ArticleStatus, DocumentFormat};
+use crate::models::error::AppError;
+use crate::service::account::AccountService;
+use crate::service::article::ArticleService;
+use crate::utils::article::parse_document;
+use crate::views::graphql::schema::current_user;
 use crate::views::graphql::types::Article;
-use async_graphql::{Context, InputObject, Object, Result};
+use async_graphql::{Context, ErrorExtensions, InputObject, Object, Result};
 use chrono::Utc;
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
@@ -12,14 +16,26 @@ use std::sync::Arc;
 pub struct CreateArticleInput {
     title: String,
     body: String,
-    publish: bool,
+    // 默认为DOCUMENT
+    format: Option<DocumentFormat>,
+    // 默认为草稿，其余状态按照从草稿变更的规则校验权限
+    status: Option<ArticleStatus>,
+    // 格式为%Y-%m-%d %H:%M:%S，UTC时间
+    publish_at: Option<String>,
     keywords: Option<String>,
     description: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub(crate) struct ArticleBody {
-    children: String,
+#[derive(InputObject, Debug)]
+pub struct UpdateArticleInput {
+    pk: String,
+    title: Option<String>,
+    body: Option<String>,
+    format: Option<DocumentFormat>,
+    keywords: Option<String>,
+    description: Option<String>,
+    // 编辑开始时读取到的版本，与服务端不一致时返回CONFLICT错误
+    expected_version: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,6 +50,15 @@ impl CreateBody {
     }
 }
 
+fn parse_publish_at(value: Option<String>) -> Result<Option<chrono::NaiveDateTime>> {
+    match value {
+        Some(value) => chrono::NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S")
+            .map(Some)
+            .map_err(|_| AppError::InvalidParameter.extend()),
+        None => Ok(None),
+    }
+}
+
 #[derive(Default)]
 pub struct ArticleMutation;
 
@@ -46,56 +71,185 @@ impl ArticleMutation {
     ) -> Result<CreateBody> {
         tracing::debug!("create_post {:?}", input);
         let state = ctx.data::<Arc<State>>().unwrap();
-        let auth = ctx
-            .data::<Option<Claims>>()
-            .map_err(|err| OtherError::Unknown(err))?;
-        if auth.is_none() {
-            return Err(async_graphql::Error::from(AppError::InvalidToken));
-        }
+        let claims = current_user(ctx)?;
 
-        let conn = state
-            .pool
-            .get()
-            .await
-            .map_err(|err| OtherError::Unknown(err))?;
-
-        let pk = nanoid!(12);
-        let article_body = ArticleBody {
-            children: input.body,
-        };
-        let publish = if input.publish { 1 } else { 0 };
         let naive_date_time = Utc::now().naive_utc();
-        let claims = auth.clone().unwrap();
-        let keywords = if let Some(v) = input.keywords {
-            v
-        } else {
-            "".to_string()
+        let mut model = ArticleModel {
+            pk: nanoid!(12),
+            title: input.title,
+            body: parse_document(&input.body, input.format.unwrap_or_default())
+                .map_err(|err| err.extend())?,
+            description: input.description.unwrap_or("".to_string()),
+            keywords: input.keywords.unwrap_or("".to_string()),
+            status: ArticleStatus::Draft,
+            publish_at: None,
+            creator: claims.user,
+            creator_nickname: "".to_string(),
+            create_time: naive_date_time,
+            update_time: naive_date_time,
+            delete_time: None,
+            views: 0,
+            version: 1,
+            slug: "".to_string(),
+            word_count: 0,
+            reading_minutes: 0,
+            excerpt: "".to_string(),
         };
-        let description = if let Some(v) = input.description {
-            v
-        } else {
-            "".to_string()
-        };
-        conn.execute(
-            "insert into articles(pk, title, body, create_time, update_time, creator, 
-                keywords, description, status, template)
-    values($1, $2, $3, $4, $5, $6, $7, $8, $9, 1);",
-            &[
-                &pk,
-                &input.title,
-                &postgres_types::Json::<ArticleBody>(article_body),
-                &naive_date_time,
-                &naive_date_time,
-                &claims.user,
-                &keywords,
-                &description,
-                &publish,
-            ],
-        )
-        .await
-        .map_err(|err| AppError::Postgresql(err))?;
+        let article_service = ArticleService::new(state.clone());
+        let status = input.status.unwrap_or(ArticleStatus::Draft);
+        if status != ArticleStatus::Draft {
+            let actor = AccountService::new(state.clone())
+                .find(&model.creator)
+                .await
+                .map_err(|err| err.extend())?;
+            article_service
+                .check_transition(&model, status, &actor)
+                .map_err(|err| err.extend())?;
+            article_service
+                .apply_status(&mut model, status, parse_publish_at(input.publish_at)?)
+                .map_err(|err| err.extend())?;
+        }
+        let pk = article_service
+            .create(model)
+            .await
+            .map_err(|err| err.extend())?
+            .pk;
 
         let result = CreateBody { pk: pk };
         Ok(result)
     }
+
+    pub async fn update_article(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateArticleInput,
+    ) -> Result<Article> {
+        tracing::debug!("update_article {:?}", input);
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        let mut model = article_service
+            .find_editable(&input.pk, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        article_service
+            .check_version(&model, input.expected_version)
+            .map_err(|err| err.extend())?;
+        if let Some(title) = input.title {
+            model.title = title;
+        }
+        if let Some(body) = input.body {
+            model.body = parse_document(&body, input.format.unwrap_or_default())
+                .map_err(|err| err.extend())?;
+        }
+        if let Some(keywords) = input.keywords {
+            model.keywords = keywords;
+        }
+        if let Some(description) = input.description {
+            model.description = description;
+        }
+        let model = article_service
+            .update_content(model, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(Article::from(model))
+    }
+
+    pub async fn restore_revision(
+        &self,
+        ctx: &Context<'_>,
+        pk: String,
+        revision: String,
+    ) -> Result<Article> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        let model = article_service
+            .find_editable(&pk, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        let model = article_service
+            .restore_revision(model, &revision, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(Article::from(model))
+    }
+
+    pub async fn transition_article(
+        &self,
+        ctx: &Context<'_>,
+        pk: String,
+        status: ArticleStatus,
+        publish_at: Option<String>,
+    ) -> Result<Article> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let actor = AccountService::new(state.clone())
+            .find(&claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        let article_service = ArticleService::new(state.clone());
+        let model = article_service
+            .find(&pk)
+            .await
+            .map_err(|err| err.extend())?;
+        let model = article_service
+            .transition(model, status, parse_publish_at(publish_at)?, &actor)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(Article::from(model))
+    }
+
+    pub async fn publish_article(&self, ctx: &Context<'_>, pk: String) -> Result<Article> {
+        self.transition_article(ctx, pk, ArticleStatus::Published, None)
+            .await
+    }
+
+    pub async fn unpublish_article(&self, ctx: &Context<'_>, pk: String) -> Result<Article> {
+        self.transition_article(ctx, pk, ArticleStatus::Draft, None)
+            .await
+    }
+
+    // 软删除，可通过restoreArticle恢复
+    pub async fn delete_article(&self, ctx: &Context<'_>, pk: String) -> Result<Article> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        let mut model = article_service
+            .find_owned(&pk, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        model.delete_time = Some(Utc::now().naive_utc());
+        let model = article_service
+            .update(model)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(Article::from(model))
+    }
+
+    pub async fn restore_article(&self, ctx: &Context<'_>, pk: String) -> Result<Article> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        let mut model = article_service
+            .find_owned(&pk, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        model.delete_time = None;
+        let model = article_service
+            .update(model)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(Article::from(model))
+    }
 }
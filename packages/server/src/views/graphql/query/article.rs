@@ -1,9 +1,12 @@
-use async_graphql::{Context, Object, Result};
+use async_graphql::{Context, ErrorExtensions, Object, Result};
 use std::sync::Arc;
 
 use crate::handlers::State;
+use crate::models::error::AppError;
 use crate::service::article::ArticleService;
-use crate::views::graphql::types::Article;
+use crate::utils::diff::diff_documents;
+use crate::views::graphql::schema::current_user;
+use crate::views::graphql::types::{Article, ArticleRevision, RenderCacheStats, RevisionDiff};
 
 #[derive(Default)]
 pub struct ArticleQuery;
@@ -24,24 +27,81 @@ impl ArticleQuery {
 
         let articles = article_service
             .query_articles(offset_value, limit_value)
-            .await?;
+            .await
+            .map_err(|err| err.extend())?;
 
-        let mut result: Vec<Article> = Vec::new();
-
-        for art in articles {
-            let model = Article { title: art.title };
-            result.push(model);
-        }
-
-        Ok(result)
+        Ok(articles.into_iter().map(Article::from).collect())
     }
 
     async fn articles_count(&self, ctx: &Context<'_>) -> Result<i32> {
         let state = ctx.data::<Arc<State>>().unwrap();
         let article_service = ArticleService::new(state.clone());
 
-        let count = article_service.query_count().await?;
+        let count = article_service
+            .query_count()
+            .await
+            .map_err(|err| err.extend())?;
 
         Ok(count as i32)
     }
+
+    // 修订历史仅文章作者可见，需要通过/graphql/mutation携带token访问
+    async fn article_revisions(
+        &self,
+        ctx: &Context<'_>,
+        pk: String,
+    ) -> Result<Vec<ArticleRevision>> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        article_service
+            .find_owned(&pk, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+        let revisions = article_service
+            .list_revisions(&pk)
+            .await
+            .map_err(|err| err.extend())?;
+
+        Ok(revisions.into_iter().map(ArticleRevision::from).collect())
+    }
+
+    async fn article_revision_diff(
+        &self,
+        ctx: &Context<'_>,
+        from: String,
+        to: String,
+    ) -> Result<Vec<RevisionDiff>> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        let claims = current_user(ctx)?;
+
+        let article_service = ArticleService::new(state.clone());
+        let from_revision = article_service
+            .find_revision(&from)
+            .await
+            .map_err(|err| err.extend())?;
+        let to_revision = article_service
+            .find_revision(&to)
+            .await
+            .map_err(|err| err.extend())?;
+        if from_revision.article != to_revision.article {
+            return Err(AppError::InvalidParameter.extend());
+        }
+        article_service
+            .find_owned(&from_revision.article, &claims.user)
+            .await
+            .map_err(|err| err.extend())?;
+
+        let diffs = diff_documents(&from_revision.body, &to_revision.body);
+        Ok(diffs.into_iter().map(RevisionDiff::from).collect())
+    }
+
+    // 文章渲染缓存的命中情况，需要登录
+    async fn render_cache_stats(&self, ctx: &Context<'_>) -> Result<RenderCacheStats> {
+        let state = ctx.data::<Arc<State>>().unwrap();
+        current_user(ctx)?;
+
+        Ok(state.render_cache.stats().into())
+    }
 }
@@ -1,5 +1,5 @@
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Schema};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::response::{Html, IntoResponse};
 use axum::Extension;
@@ -7,6 +7,7 @@ use std::sync::Arc;
 
 use crate::handlers::State;
 use crate::models::claims::Claims;
+use crate::models::error::{AppError, OtherError};
 use crate::views::graphql::mutation::MutationRoot;
 use crate::views::graphql::query::QueryRoot;
 
@@ -26,6 +27,13 @@ pub async fn graphql_mutation_handler<'a>(
     schema.execute(req.into_inner()).await.into()
 }
 
+pub fn current_user(ctx: &Context<'_>) -> async_graphql::Result<Claims> {
+    let auth = ctx.data::<Option<Claims>>().map_err(OtherError::Unknown)?;
+
+    auth.clone()
+        .ok_or_else(|| async_graphql::Error::from(AppError::InvalidToken))
+}
+
 pub async fn graphql_mutation_playground() -> impl IntoResponse {
     Html(playground_source(GraphQLPlaygroundConfig::new(
         "/graphql/mutation",
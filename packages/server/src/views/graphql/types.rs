@@ -1,13 +1,212 @@
-use async_graphql::Object;
+use async_graphql::{Enum, Object, SimpleObject};
+
+use crate::models::article::{ArticleModel, ArticleStatus};
+use crate::models::revision::RevisionModel;
+use crate::utils::diff::{DiffOp, NodeDiff};
+use crate::utils::export;
+use crate::utils::render_cache;
 
 #[derive(Debug, Clone)]
 pub struct Article {
-    pub title: String,
+    pub model: ArticleModel,
+}
+
+impl From<ArticleModel> for Article {
+    fn from(model: ArticleModel) -> Self {
+        Article { model }
+    }
 }
 
 #[Object]
 impl Article {
+    async fn pk(&self) -> String {
+        self.model.pk.clone()
+    }
+
     async fn title(&self) -> String {
-        self.title.clone()
+        self.model.title.clone()
+    }
+
+    async fn slug(&self) -> String {
+        self.model.slug.clone()
+    }
+
+    // 站内的规范地址
+    async fn path(&self) -> String {
+        self.model.path()
+    }
+
+    async fn body(&self) -> String {
+        self.model.body.to_string()
+    }
+
+    // 正文导出为CommonMark
+    async fn markdown(&self) -> String {
+        export::document_to_markdown(&self.model.body)
+    }
+
+    async fn plain_text(&self) -> String {
+        export::document_to_text(&self.model.body)
+    }
+
+    async fn description(&self) -> String {
+        self.model.description.clone()
+    }
+
+    async fn keywords(&self) -> String {
+        self.model.keywords.clone()
+    }
+
+    // 中日韩文字按字计数
+    async fn word_count(&self) -> i32 {
+        self.model.reading_stats().word_count
+    }
+
+    async fn reading_minutes(&self) -> i32 {
+        self.model.reading_stats().reading_minutes
+    }
+
+    // 根据开头段落自动生成的摘要
+    async fn excerpt(&self) -> String {
+        self.model.reading_stats().excerpt
+    }
+
+    async fn status(&self) -> ArticleStatus {
+        self.model.status
+    }
+
+    async fn publish_at(&self) -> Option<String> {
+        self.model
+            .publish_at
+            .map(|publish_at| publish_at.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    async fn creator(&self) -> String {
+        self.model.creator.clone()
+    }
+
+    async fn create_time(&self) -> String {
+        self.model
+            .create_time
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    async fn update_time(&self) -> String {
+        self.model
+            .update_time
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    async fn deleted(&self) -> bool {
+        self.model.delete_time.is_some()
+    }
+
+    async fn version(&self) -> i64 {
+        self.model.version
+    }
+
+    async fn etag(&self) -> String {
+        self.model.etag()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArticleRevision {
+    pub model: RevisionModel,
+}
+
+impl From<RevisionModel> for ArticleRevision {
+    fn from(model: RevisionModel) -> Self {
+        ArticleRevision { model }
+    }
+}
+
+#[Object]
+impl ArticleRevision {
+    async fn pk(&self) -> String {
+        self.model.pk.clone()
+    }
+
+    async fn article(&self) -> String {
+        self.model.article.clone()
+    }
+
+    async fn title(&self) -> String {
+        self.model.title.clone()
+    }
+
+    async fn body(&self) -> String {
+        self.model.body.to_string()
+    }
+
+    async fn description(&self) -> String {
+        self.model.description.clone()
+    }
+
+    async fn keywords(&self) -> String {
+        self.model.keywords.clone()
+    }
+
+    async fn author(&self) -> String {
+        self.model.author.clone()
+    }
+
+    async fn create_time(&self) -> String {
+        self.model
+            .create_time
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionDiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RevisionDiff {
+    op: RevisionDiffOp,
+    // 节点的JSON字符串
+    node: String,
+    old_index: Option<i32>,
+    new_index: Option<i32>,
+}
+
+impl From<NodeDiff> for RevisionDiff {
+    fn from(diff: NodeDiff) -> Self {
+        RevisionDiff {
+            op: match diff.op {
+                DiffOp::Equal => RevisionDiffOp::Equal,
+                DiffOp::Insert => RevisionDiffOp::Insert,
+                DiffOp::Delete => RevisionDiffOp::Delete,
+            },
+            node: diff.node.to_string(),
+            old_index: diff.old_index.map(|index| index as i32),
+            new_index: diff.new_index.map(|index| index as i32),
+        }
+    }
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RenderCacheStats {
+    hits: i64,
+    misses: i64,
+    entries: i32,
+    capacity: i32,
+}
+
+impl From<render_cache::RenderCacheStats> for RenderCacheStats {
+    fn from(stats: render_cache::RenderCacheStats) -> Self {
+        RenderCacheStats {
+            hits: stats.hits as i64,
+            misses: stats.misses as i64,
+            entries: stats.entries as i32,
+            capacity: stats.capacity as i32,
+        }
     }
 }
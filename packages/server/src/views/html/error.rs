@@ -47,7 +47,7 @@ where
 impl From<AppError> for HttpHTMLError {
     fn from(error: AppError) -> Self {
         match error {
-            WrongCredentials => HttpHTMLError::new("授权有误"),
+            _ => HttpHTMLError::new("授权有误"),
         }
     }
 }
@@ -11,11 +11,10 @@ use crate::handlers::State;
 use crate::models::error::{AppError, OtherError};
 use crate::models::index::IndexModel;
 use crate::service::index::IndexService;
+use crate::utils::metadata::PageMeta;
 use crate::views::restful::error::HttpRESTError;
 use crate::{helpers, layers};
 
-const INDEX_PAGE_SIZE: i32 = 10;
-
 #[derive(Deserialize)]
 pub struct IndexQuery {
     p: Option<i32>,
@@ -30,32 +29,40 @@ pub async fn index_handler<'a>(
     if current_page < 1 {
         return Err(HttpRESTError::from(AppError::InvalidParameter));
     }
+    let page_size = state.config.current().index_page_size;
 
     let index_service = IndexService::new(state.clone());
     let count = index_service.query_count().await?;
 
     let row_count = count as i32;
-    let mut max_page = row_count / INDEX_PAGE_SIZE;
-    if row_count % INDEX_PAGE_SIZE != 0 {
+    let mut max_page = row_count / page_size;
+    if row_count % page_size != 0 {
         max_page += 1;
     }
     if current_page > max_page {
         current_page = max_page;
     }
 
-    let offset: i64 = ((current_page - 1) * INDEX_PAGE_SIZE) as i64;
-    let limit: i64 = INDEX_PAGE_SIZE as i64;
+    let offset: i64 = ((current_page - 1) * page_size) as i64;
+    let limit: i64 = page_size as i64;
 
     let models = index_service.query(offset, limit).await?;
 
     let pages_html = helpers::calc_page_html(max_page, current_page);
+    let config = state.config.current();
+    let path = if current_page > 1 {
+        format!("/?p={}", current_page)
+    } else {
+        "/".to_string()
+    };
+    let page_meta = PageMeta::website(&config, &config.site_name, &path, &config.site_name);
     let result = state
         .registry
         .render(
             "index",
-            &json!({ "models": models, "pages_html": pages_html }),
+            &json!({ "models": models, "pages_html": pages_html, "page_meta": page_meta }),
         )
-        .map_err(|err| AppError::Handlebars(err))?;
+        .map_err(AppError::Handlebars)?;
 
     Ok(Html(result))
 }
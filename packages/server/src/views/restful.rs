@@ -1,2 +1,3 @@
+pub(crate) mod article;
 pub(crate) mod error;
 pub(crate) mod index;
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::{header, HeaderMap};
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Deserialize;
+
+use crate::handlers::State;
+use crate::models::article::{ArticleModel, DocumentFormat};
+use crate::models::claims::Claims;
+use crate::models::error::AppError;
+use crate::service::article::ArticleService;
+use crate::utils::article::parse_document;
+use crate::views::restful::error::HttpRESTError;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleUpdateIn {
+    title: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    format: DocumentFormat,
+    keywords: Option<String>,
+    description: Option<String>,
+    expected_version: Option<i64>,
+}
+
+fn with_etag(model: ArticleModel) -> impl IntoResponse {
+    ([(header::ETAG, model.etag())], Json(model))
+}
+
+// If-Match: "3" 或 W/"3"，* 表示不检查版本
+fn parse_if_match(headers: &HeaderMap) -> Result<Option<i64>, AppError> {
+    let value = match headers.get(header::IF_MATCH) {
+        Some(value) => value.to_str().map_err(|_| AppError::InvalidParameter)?,
+        None => return Ok(None),
+    };
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<i64>()
+        .map(Some)
+        .map_err(|_| AppError::InvalidParameter)
+}
+
+// 作者可以读取自己未发布的文章
+pub async fn read(
+    Path(pk): Path<String>,
+    claims: Option<Claims>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<impl IntoResponse, HttpRESTError> {
+    let model = ArticleService::new(state.clone()).find(&pk).await?;
+    let is_owner = claims.map_or(false, |claims| claims.user == model.creator);
+    if model.delete_time.is_some() || !(model.is_readable() || is_owner) {
+        return Err(HttpRESTError::from(AppError::NotFound));
+    }
+
+    Ok(with_etag(model))
+}
+
+pub async fn update(
+    Path(pk): Path<String>,
+    claims: Claims,
+    headers: HeaderMap,
+    Extension(state): Extension<Arc<State>>,
+    Json(input): Json<ArticleUpdateIn>,
+) -> Result<impl IntoResponse, HttpRESTError> {
+    tracing::debug!("update article {} {:?}", pk, input);
+    let expected_version = parse_if_match(&headers)?.or(input.expected_version);
+
+    let article_service = ArticleService::new(state.clone());
+    let mut model = article_service.find_editable(&pk, &claims.user).await?;
+    article_service.check_version(&model, expected_version)?;
+    if let Some(title) = input.title {
+        model.title = title;
+    }
+    if let Some(body) = input.body {
+        model.body = parse_document(&body, input.format)?;
+    }
+    if let Some(keywords) = input.keywords {
+        model.keywords = keywords;
+    }
+    if let Some(description) = input.description {
+        model.description = description;
+    }
+    let model = article_service.update_content(model, &claims.user).await?;
+
+    Ok(with_etag(model))
+}
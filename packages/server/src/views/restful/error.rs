@@ -12,6 +12,8 @@ use std::fmt::{Debug, Display, Formatter};
 pub struct HttpRESTError {
     pub status: StatusCode,
     pub message: String,
+    // 附加在响应体中的结构化信息
+    pub details: Option<serde_json::Value>,
 }
 
 impl HttpRESTError {
@@ -19,12 +21,21 @@ impl HttpRESTError {
         HttpRESTError {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             message: message.to_string(),
+            details: None,
         }
     }
     pub fn from_string(message: String) -> HttpRESTError {
         HttpRESTError {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             message,
+            details: None,
+        }
+    }
+    pub fn with_status(status: StatusCode, message: String) -> HttpRESTError {
+        HttpRESTError {
+            status,
+            message,
+            details: None,
         }
     }
 }
@@ -47,16 +58,40 @@ where
 impl From<AppError> for HttpRESTError {
     fn from(error: AppError) -> Self {
         match error {
-            WrongCredentials => HttpRESTError::new("授权有误"),
+            AppError::NotFound => {
+                HttpRESTError::with_status(StatusCode::NOT_FOUND, error.to_string())
+            }
+            AppError::Forbidden => {
+                HttpRESTError::with_status(StatusCode::FORBIDDEN, error.to_string())
+            }
+            AppError::InvalidParameter
+            | AppError::InvalidData
+            | AppError::InvalidTransition { .. } => {
+                HttpRESTError::with_status(StatusCode::BAD_REQUEST, error.to_string())
+            }
+            AppError::Conflict { current_version } => HttpRESTError {
+                status: StatusCode::CONFLICT,
+                message: error.to_string(),
+                details: Some(json!({ "currentVersion": current_version })),
+            },
+            AppError::SlugTaken => {
+                HttpRESTError::with_status(StatusCode::CONFLICT, error.to_string())
+            }
+            _ => HttpRESTError::new("授权有误"),
         }
     }
 }
 
 impl IntoResponse for HttpRESTError {
     fn into_response(self) -> Response {
-        let body = Json(json!({
+        let mut body = json!({
             "error": self.message,
-        }));
-        (self.status, body).into_response()
+        });
+        if let Some(serde_json::Value::Object(details)) = self.details {
+            for (key, value) in details {
+                body[key] = value;
+            }
+        }
+        (self.status, Json(body)).into_response()
     }
 }
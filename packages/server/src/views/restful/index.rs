@@ -13,8 +13,6 @@ use crate::service::index::IndexService;
 use crate::views::restful::error::HttpRESTError;
 use crate::{helpers, layers};
 
-const INDEX_PAGE_SIZE: i32 = 10;
-
 #[derive(Deserialize)]
 pub struct IndexIn {
     p: Option<i32>,
@@ -34,18 +32,19 @@ pub async fn query(
     if current_page < 1 {
         return Err(HttpRESTError::from(AppError::InvalidParameter));
     }
+    let page_size = state.config.current().index_page_size;
 
     let row_count = 17;
-    let mut max_page = row_count / INDEX_PAGE_SIZE;
-    if row_count % INDEX_PAGE_SIZE != 0 {
+    let mut max_page = row_count / page_size;
+    if row_count % page_size != 0 {
         max_page += 1;
     }
     if current_page > max_page {
         current_page = max_page;
     }
 
-    let offset: i64 = ((current_page - 1) * INDEX_PAGE_SIZE) as i64;
-    let limit: i64 = INDEX_PAGE_SIZE as i64;
+    let offset: i64 = ((current_page - 1) * page_size) as i64;
+    let limit: i64 = page_size as i64;
 
     let index_service = IndexService::new(state.clone());
 